@@ -0,0 +1,46 @@
+//! 从 Unihan 数据库的 kCantonese 读音生成粤拼读音表：
+//!
+//! ```sh
+//! cargo run --example jyutping_table -- Unihan_Readings.txt > dict/jyutping.txt
+//! ```
+//!
+//! `Unihan_Readings.txt` 在 <https://www.unicode.org/Public/UCD/latest/ucd/Unihan.zip> 中。
+//! 收录所有有粤语读音的字，包括繁体字；多音字取第一个读音，
+//! 现有读音表中手工挑选过的读音保留不变
+use std::{collections::BTreeMap, env, fs};
+use anyhow::{Context, Result};
+
+const CURRENT: &str = include_str!("../dict/jyutping.txt");
+
+fn main() -> Result<()> {
+    let path = env::args().nth(1).context("缺少参数: Unihan_Readings.txt 的路径")?;
+    let content = fs::read_to_string(&path).with_context(|| format!("无法读取 {}", path))?;
+
+    let mut table = BTreeMap::new();
+    for line in content.lines().filter(|line| !line.starts_with('#')) {
+        let mut fields = line.split('\t');
+        let (codepoint, readings) = match (fields.next(), fields.next(), fields.next()) {
+            (Some(codepoint), Some("kCantonese"), Some(readings)) => (codepoint, readings),
+            _ => continue,
+        };
+        let cp = codepoint.strip_prefix("U+")
+            .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+            .and_then(char::from_u32)
+            .with_context(|| format!("码位格式错误: {}", codepoint))?;
+        if let Some(reading) = readings.split_whitespace().next() {
+            table.insert(cp, reading.to_string());
+        }
+    }
+    for line in CURRENT.lines().filter(|line| !line.starts_with('#')) {
+        let mut it = line.split_whitespace();
+        if let (Some(cp), Some(reading)) = (it.next().and_then(|s| s.chars().next()), it.next()) {
+            table.insert(cp, reading.to_string());
+        }
+    }
+
+    println!("# 粤拼读音表：每行一个汉字及其粤拼（带声调），由 examples/jyutping_table.rs 从 Unihan kCantonese 生成");
+    for (cp, reading) in table {
+        println!("{} {}", cp, reading);
+    }
+    Ok(())
+}
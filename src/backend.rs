@@ -0,0 +1,211 @@
+use std::{fmt::Display, io::{stdout, Stdout, Write}};
+use anyhow::Result;
+use crossterm::{cursor::{self, CursorShape, MoveTo}, execute, style::{ContentStyle, Print, PrintStyledContent, StyledContent}, terminal::{self, Clear}};
+
+/// 界面的绘制目标，Tui 只通过它读写终端
+pub trait Backend {
+    fn size(&self) -> Result<(u16, u16)>;
+    fn enable_raw_mode(&mut self) -> Result<()>;
+    fn disable_raw_mode(&mut self) -> Result<()>;
+    fn clear(&mut self) -> Result<()>;
+    fn move_to(&mut self, col: u16, row: u16) -> Result<()>;
+    /// 在光标处输出，光标移动到输出内容之后
+    fn print<D: Display>(&mut self, content: D) -> Result<()> {
+        self.print_styled(ContentStyle::new().apply(content))
+    }
+    fn print_styled<D: Display>(&mut self, content: StyledContent<D>) -> Result<()>;
+    fn show_cursor(&mut self, shape: CursorShape) -> Result<()>;
+    fn hide_cursor(&mut self) -> Result<()>;
+    fn flush(&mut self) -> Result<()>;
+}
+
+/// 输出到真实终端
+pub struct CrosstermBackend {
+    stdout: Stdout,
+}
+
+impl CrosstermBackend {
+    pub fn new() -> Self {
+        Self { stdout: stdout() }
+    }
+}
+
+impl Backend for CrosstermBackend {
+    fn size(&self) -> Result<(u16, u16)> {
+        Ok(terminal::size()?)
+    }
+
+    fn enable_raw_mode(&mut self) -> Result<()> {
+        Ok(terminal::enable_raw_mode()?)
+    }
+
+    fn disable_raw_mode(&mut self) -> Result<()> {
+        Ok(terminal::disable_raw_mode()?)
+    }
+
+    fn clear(&mut self) -> Result<()> {
+        Ok(execute!(self.stdout, Clear(terminal::ClearType::All))?)
+    }
+
+    fn move_to(&mut self, col: u16, row: u16) -> Result<()> {
+        Ok(execute!(self.stdout, MoveTo(col, row))?)
+    }
+
+    fn print<D: Display>(&mut self, content: D) -> Result<()> {
+        Ok(execute!(self.stdout, Print(content))?)
+    }
+
+    fn print_styled<D: Display>(&mut self, content: StyledContent<D>) -> Result<()> {
+        Ok(execute!(self.stdout, PrintStyledContent(content))?)
+    }
+
+    fn show_cursor(&mut self, shape: CursorShape) -> Result<()> {
+        Ok(execute!(self.stdout, cursor::Show, cursor::SetCursorShape(shape))?)
+    }
+
+    fn hide_cursor(&mut self) -> Result<()> {
+        Ok(execute!(self.stdout, cursor::Hide)?)
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        Ok(self.stdout.flush()?)
+    }
+}
+
+/// 屏幕上的一格，宽字符之后的一格为 `\0`
+#[cfg(test)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Cell {
+    pub ch: char,
+    pub style: ContentStyle,
+}
+
+/// 输出到内存中的屏幕，用于在测试中检查界面
+#[cfg(test)]
+pub struct BufferBackend {
+    pub cols: u16,
+    pub rows: u16,
+    pub cells: Vec<Vec<Cell>>,
+    pub cursor: (u16, u16),
+    pub cursor_visible: bool,
+    pub raw_mode: bool,
+}
+
+#[cfg(test)]
+impl BufferBackend {
+    pub fn new(cols: u16, rows: u16) -> Self {
+        Self {
+            cols,
+            rows,
+            cells: vec![vec![Cell { ch: ' ', style: ContentStyle::new() }; cols as usize]; rows as usize],
+            cursor: (0, 0),
+            cursor_visible: true,
+            raw_mode: false,
+        }
+    }
+
+    /// 改变屏幕大小，内容清空
+    pub fn set_size(&mut self, cols: u16, rows: u16) {
+        *self = Self { raw_mode: self.raw_mode, ..Self::new(cols, rows) };
+    }
+
+    /// 第 row 行的文字，去掉行尾空格
+    pub fn line(&self, row: u16) -> String {
+        let line: String = self.cells[row as usize].iter().map(|cell| cell.ch).filter(|ch| *ch != '\0').collect();
+        line.trim_end().to_string()
+    }
+
+    pub fn cell(&self, col: u16, row: u16) -> Cell {
+        self.cells[row as usize][col as usize]
+    }
+
+    /// 整个屏幕的文字，便于查找
+    pub fn text(&self) -> String {
+        (0..self.rows).map(|row| self.line(row)).collect::<Vec<_>>().join("\n")
+    }
+}
+
+#[cfg(test)]
+impl Backend for BufferBackend {
+    fn size(&self) -> Result<(u16, u16)> {
+        Ok((self.cols, self.rows))
+    }
+
+    fn enable_raw_mode(&mut self) -> Result<()> {
+        self.raw_mode = true;
+        Ok(())
+    }
+
+    fn disable_raw_mode(&mut self) -> Result<()> {
+        self.raw_mode = false;
+        Ok(())
+    }
+
+    fn clear(&mut self) -> Result<()> {
+        let cursor = self.cursor;
+        self.set_size(self.cols, self.rows);
+        self.cursor = cursor;
+        Ok(())
+    }
+
+    fn move_to(&mut self, col: u16, row: u16) -> Result<()> {
+        self.cursor = (col, row);
+        Ok(())
+    }
+
+    fn print_styled<D: Display>(&mut self, content: StyledContent<D>) -> Result<()> {
+        use unicode_width::UnicodeWidthChar;
+        let style = *content.style();
+        for ch in content.content().to_string().chars() {
+            let (col, row) = self.cursor;
+            let width = ch.width().unwrap_or(0) as u16;
+            if width == 0 {
+                continue;
+            }
+            if col < self.cols && row < self.rows {
+                self.cells[row as usize][col as usize] = Cell { ch, style };
+                if width == 2 && col + 1 < self.cols {
+                    self.cells[row as usize][col as usize + 1] = Cell { ch: '\0', style };
+                }
+            }
+            self.cursor = (col + width, row);
+        }
+        Ok(())
+    }
+
+    fn show_cursor(&mut self, _shape: CursorShape) -> Result<()> {
+        self.cursor_visible = true;
+        Ok(())
+    }
+
+    fn hide_cursor(&mut self) -> Result<()> {
+        self.cursor_visible = false;
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crossterm::style::{Color, Stylize};
+
+    #[test]
+    fn test_buffer() {
+        let mut backend = BufferBackend::new(10, 2);
+        backend.move_to(1, 0).unwrap();
+        backend.print("长城a").unwrap();
+        backend.print_styled("b".with(Color::Red)).unwrap();
+        assert_eq!(backend.line(0), " 长城ab");
+        assert_eq!(backend.cursor, (7, 0));
+        assert_eq!(backend.cell(6, 0).style.foreground_color, Some(Color::Red));
+        backend.move_to(8, 1).unwrap();
+        backend.print("xyz").unwrap();
+        assert_eq!(backend.line(1), "        xy");
+        backend.clear().unwrap();
+        assert_eq!(backend.text(), "\n");
+    }
+}
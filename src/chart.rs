@@ -0,0 +1,68 @@
+/// 盲文字符中每个点对应的位，按 [行][列] 排列
+const DOTS: [[u32; 2]; 4] = [[0x01, 0x08], [0x02, 0x10], [0x04, 0x20], [0x40, 0x80]];
+
+/// 把 values 缩放到 len 个值：多于 len 个时按区间取平均，少于 len 个时拉伸
+pub fn resample(values: &[f64], len: usize) -> Vec<f64> {
+    let n = values.len();
+    (0..len).map(|idx| {
+        let start = idx * n / len;
+        let end = ((idx + 1) * n / len).max(start + 1).min(n);
+        if start >= end {
+            return 0.0;
+        }
+        values[start..end].iter().sum::<f64>() / (end - start) as f64
+    }).collect()
+}
+
+/// 用盲文点阵画折线图，每个字符 2x4 个点，纵轴从 0 到最大值，返回从上到下的 height 行
+pub fn braille(values: &[f64], width: usize, height: usize) -> Vec<String> {
+    let (dots_x, dots_y) = (width * 2, height * 4);
+    let max = values.iter().cloned().fold(0.0, f64::max);
+    let ys: Vec<usize> = resample(values, dots_x).iter().map(|value| {
+        if max > 0.0 {
+            (value / max * (dots_y - 1) as f64).round() as usize
+        } else {
+            0
+        }
+    }).collect();
+
+    let mut cells = vec![vec![0; width]; height];
+    for (x, &y) in ys.iter().enumerate() {
+        // 与前一个点竖直连起来，陡峭处不断线
+        let prev = if x > 0 { ys[x - 1] } else { y };
+        for y in y.min(prev)..=y.max(prev) {
+            let row = dots_y - 1 - y;
+            cells[row / 4][x / 2] |= DOTS[row % 4][x % 2];
+        }
+    }
+    cells.iter().map(|line| {
+        line.iter().map(|bits| char::from_u32(0x2800 + bits).unwrap()).collect()
+    }).collect()
+}
+
+/// 与 `braille` 同宽的标记行，对应的秒内有错误时标出 mark
+pub fn marks(errors: &[usize], width: usize, mark: char) -> String {
+    let errors: Vec<f64> = errors.iter().map(|count| *count as f64).collect();
+    resample(&errors, width).iter().map(|count| if *count > 0.0 { mark } else { ' ' }).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_resample() {
+        assert_eq!(resample(&[1.0, 3.0, 5.0, 7.0], 2), vec![2.0, 6.0]);
+        assert_eq!(resample(&[1.0, 2.0], 4), vec![1.0, 1.0, 2.0, 2.0]);
+        assert_eq!(resample(&[], 2), vec![0.0, 0.0]);
+    }
+
+    #[test]
+    fn test_braille() {
+        // 四个点从底部升到顶部，再回到底部
+        let lines = braille(&[0.0, 1.0, 2.0, 3.0, 0.0, 0.0], 3, 1);
+        assert_eq!(lines, vec!["⣠⠞⣇".to_string()]);
+        assert_eq!(braille(&[0.0, 0.0], 1, 2), vec!["⠀", "⣀"]);
+        assert_eq!(marks(&[0, 2, 0, 0, 1, 0], 3, 'x'), "x x");
+    }
+}
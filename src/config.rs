@@ -0,0 +1,215 @@
+use std::{collections::HashMap, fs, path::PathBuf, time::Duration};
+use anyhow::{Context, Result};
+use crossterm::cursor::CursorShape;
+use serde::Deserialize;
+use typlap::{corpus, utils::util::Scheme};
+use crate::{keys::KeyBindings, theme::ThemeName};
+
+/// 配置文件，位于 `$XDG_CONFIG_HOME/typlap/config.toml`，不存在时使用默认配置
+///
+/// ```toml
+/// corpus = "chengyu"
+/// scheme = "zhuyin"
+/// length = 30
+/// cursor = "block"
+/// theme = "colorblind"
+/// idle_pause = 30
+/// time = 60
+/// ```
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// 默认词库，命令行参数优先
+    pub corpus: String,
+    /// 默认输入方案，命令行参数优先
+    pub scheme: Scheme,
+    /// 每轮练习的词数，一页放不下时分页，不设置时填满文本区域
+    pub length: Option<usize>,
+    pub cursor: Cursor,
+    pub theme: ThemeName,
+    /// 超过多少秒没有输入时自动暂停，0 表示不自动暂停
+    pub idle_pause: u64,
+    /// 限时练习的秒数，不设置时输入完文本才结束
+    pub time: Option<u64>,
+    pub punctuation: PunctuationConfig,
+    pub sound: SoundConfig,
+    pub keys: KeyBindings,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            corpus: String::from(corpus::DEFAULT),
+            scheme: Scheme::Pinyin,
+            length: None,
+            cursor: Cursor::Line,
+            theme: ThemeName::Default,
+            idle_pause: 10,
+            time: None,
+            punctuation: PunctuationConfig::default(),
+            sound: SoundConfig::default(),
+            keys: KeyBindings::default(),
+        }
+    }
+}
+
+/// 光标形状
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Cursor {
+    Line,
+    Block,
+    Underscore,
+}
+
+impl From<Cursor> for CursorShape {
+    fn from(cursor: Cursor) -> Self {
+        match cursor {
+            Cursor::Line => CursorShape::Line,
+            Cursor::Block => CursorShape::Block,
+            Cursor::Underscore => CursorShape::UnderScore,
+        }
+    }
+}
+
+/// 中文标点的转换方式
+///
+/// ```toml
+/// [punctuation]
+/// full_width = false
+///
+/// [punctuation.table]
+/// "、" = "\\"
+/// "……" = "^"
+/// ```
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PunctuationConfig {
+    /// 需要使用中文输入法输入全角标点，不再转换为英文标点
+    pub full_width: bool,
+    /// 覆盖或补充默认的转换表，键可以是多个字符
+    pub table: HashMap<String, String>,
+}
+
+/// 按键音
+///
+/// ```toml
+/// [sound]
+/// enabled = true
+/// pack = "sounds/mechanical"
+/// volume = 0.5
+/// pitch = 800.0
+/// ```
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SoundConfig {
+    /// 是否播放按键音，命令行 `--no-sound` 优先
+    pub enabled: bool,
+    /// 音效包目录，包含 correct、error、space、enter、finish 等声音文件，相对路径相对于配置目录
+    pub pack: Option<PathBuf>,
+    /// 音量，0.0 到 1.0
+    pub volume: f32,
+    /// 没有音效包时合成声音的基准音高（Hz）
+    pub pitch: f32,
+}
+
+impl Default for SoundConfig {
+    fn default() -> Self {
+        Self { enabled: true, pack: None, volume: 1.0, pitch: 1000.0 }
+    }
+}
+
+impl Config {
+    pub fn path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("typlap").join("config.toml"))
+    }
+
+    pub fn load() -> Result<Self> {
+        match Self::path() {
+            Some(path) if path.exists() => {
+                let content = fs::read_to_string(&path)?;
+                let config: Self = toml::from_str(&content).with_context(|| format!("配置文件格式错误: {}", path.display()))?;
+                config.keys.validate().with_context(|| format!("配置文件格式错误: {}", path.display()))?;
+                Ok(config)
+            }
+            _ => Ok(Self::default()),
+        }
+    }
+
+    /// 音效包目录的完整路径
+    pub fn sound_pack(&self) -> Option<PathBuf> {
+        let pack = self.sound.pack.as_ref()?;
+        match Self::path().as_ref().and_then(|path| path.parent()) {
+            Some(dir) => Some(dir.join(pack)),
+            None => Some(pack.clone()),
+        }
+    }
+
+    /// 自动暂停前允许的空闲时间
+    pub fn idle_pause(&self) -> Option<Duration> {
+        match self.idle_pause {
+            0 => None,
+            secs => Some(Duration::from_secs(secs)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let config: Config = toml::from_str(r#"
+            corpus = "chengyu"
+            scheme = "bopomofo"
+            length = 30
+            cursor = "block"
+            theme = "high-contrast"
+            idle_pause = 0
+            time = 60
+
+            [punctuation]
+            full_width = true
+
+            [punctuation.table]
+            "、" = "\\"
+
+            [sound]
+            pack = "/usr/share/typlap/click"
+            volume = 0.5
+
+            [keys]
+            quit = "ctrl-q"
+        "#).unwrap();
+        assert_eq!(config.corpus, "chengyu");
+        assert_eq!(config.scheme, Scheme::Zhuyin);
+        assert_eq!(config.length, Some(30));
+        assert_eq!(config.cursor, Cursor::Block);
+        assert_eq!(config.theme, ThemeName::HighContrast);
+        assert_eq!(config.idle_pause(), None);
+        assert_eq!(config.time, Some(60));
+        assert!(config.punctuation.full_width);
+        assert_eq!(config.punctuation.table["、"], "\\");
+        assert_eq!(config.sound_pack(), Some(PathBuf::from("/usr/share/typlap/click")));
+        assert_eq!(config.sound.volume, 0.5);
+        assert_eq!(config.keys.quit.to_string(), "ctrl-q");
+        assert_eq!(config.keys.restart.to_string(), "ctrl-r");
+
+        let config: Config = toml::from_str("").unwrap();
+        assert_eq!(config.corpus, corpus::DEFAULT);
+        assert_eq!(config.scheme, Scheme::Pinyin);
+        assert_eq!(config.length, None);
+        assert_eq!(config.idle_pause(), Some(Duration::from_secs(10)));
+        assert_eq!(config.time, None);
+        assert!(config.sound.enabled);
+        assert!(!config.punctuation.full_width);
+        assert_eq!(config.sound_pack(), None);
+        assert_eq!(config.sound.volume, 1.0);
+        assert_eq!(config.sound.pitch, 1000.0);
+        assert!(toml::from_str::<Config>("[punctuation]\nfull = true").is_err());
+        assert!(toml::from_str::<Config>("scheme = \"wubi\"").is_err());
+        assert!(toml::from_str::<Config>("[keys]\nquit = \"q\"").is_err());
+        assert_eq!(toml::from_str::<Config>("scheme = \"bopomofo\"").unwrap().scheme, Scheme::Zhuyin);
+    }
+}
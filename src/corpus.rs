@@ -0,0 +1,101 @@
+use std::{ffi::OsStr, fs, path::{Path, PathBuf}};
+use anyhow::{Context, Result};
+
+/// 编译进程序的词库
+const BUILTIN: [(&str, &str); 9] = [
+    ("animal", include_str!("../text/animal.txt")),
+    ("chengyu", include_str!("../text/chengyu.txt")),
+    ("diming", include_str!("../text/diming.txt")),
+    ("en", include_str!("../text/en.txt")),
+    ("food", include_str!("../text/food.txt")),
+    ("it", include_str!("../text/it.txt")),
+    ("japanese", include_str!("../text/japanese.txt")),
+    ("medical", include_str!("../text/medical.txt")),
+    ("poem", include_str!("../text/poem.txt")),
+];
+
+pub const DEFAULT: &str = "it";
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Origin {
+    Builtin,
+    User(PathBuf),
+}
+
+/// 用户词库目录，其中的 `<名称>.txt` 会补充或覆盖同名的内置词库
+pub fn user_dir() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("typlap").join("text"))
+}
+
+/// 所有可用的词库，按名称排序
+pub fn list() -> Vec<(String, Origin)> {
+    list_in(user_dir().as_deref())
+}
+
+fn list_in(user_dir: Option<&Path>) -> Vec<(String, Origin)> {
+    let mut corpora: Vec<(String, Origin)> = BUILTIN
+        .iter()
+        .map(|(name, _)| (name.to_string(), Origin::Builtin))
+        .collect();
+    let entries = user_dir.and_then(|dir| fs::read_dir(dir).ok());
+    for path in entries.into_iter().flatten().filter_map(|entry| entry.ok()).map(|entry| entry.path()) {
+        if path.extension() != Some(OsStr::new("txt")) {
+            continue;
+        }
+        let name = match path.file_stem().and_then(|stem| stem.to_str()) {
+            Some(name) => name.to_string(),
+            None => continue,
+        };
+        corpora.retain(|(n, _)| *n != name);
+        corpora.push((name, Origin::User(path)));
+    }
+    corpora.sort_by(|a, b| a.0.cmp(&b.0));
+    corpora
+}
+
+/// 按文件路径或词库名称读取词库，名称优先查找用户词库
+pub fn load(name: &str) -> Result<String> {
+    load_in(name, user_dir().as_deref())
+}
+
+fn load_in(name: &str, user_dir: Option<&Path>) -> Result<String> {
+    let path = Path::new(name);
+    if path.is_file() {
+        return fs::read_to_string(path).with_context(|| format!("无法读取词库: {}", name));
+    }
+    if let Some(path) = user_dir.map(|dir| dir.join(name).with_extension("txt")).filter(|path| path.is_file()) {
+        return fs::read_to_string(&path).with_context(|| format!("无法读取词库: {}", path.display()));
+    }
+    BUILTIN
+        .iter()
+        .find(|(n, _)| *n == name)
+        .map(|(_, content)| content.to_string())
+        .ok_or_else(|| anyhow::Error::msg(format!("未知的词库: {}", name)))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_load() {
+        let dir = std::env::temp_dir().join("typlap-corpus");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("it.txt"), "用户 词库").unwrap();
+        fs::write(dir.join("mine.txt"), "自己的 词库").unwrap();
+        fs::write(dir.join("notes.md"), "").unwrap();
+
+        assert_eq!(load_in("it", Some(&dir)).unwrap(), "用户 词库");
+        assert_eq!(load_in("mine", Some(&dir)).unwrap(), "自己的 词库");
+        assert!(load_in("it", None).unwrap().starts_with("字符串"));
+        assert!(load_in("missing", Some(&dir)).is_err());
+        assert_eq!(load_in(dir.join("mine.txt").to_str().unwrap(), None).unwrap(), "自己的 词库");
+
+        let corpora = list_in(Some(&dir));
+        assert_eq!(corpora.len(), BUILTIN.len() + 1);
+        assert!(corpora.contains(&(String::from("it"), Origin::User(dir.join("it.txt")))));
+        assert!(corpora.contains(&(String::from("en"), Origin::Builtin)));
+        assert!(!corpora.iter().any(|(name, _)| name == "notes"));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
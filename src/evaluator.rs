@@ -1,17 +1,68 @@
-use std::time::{Duration, Instant};
+use std::time::Duration;
+#[derive(Clone, Debug, PartialEq)]
 pub enum EvalResult {
+    /// (净准确率, 速度)
     Snap(f64, f64),
-    Done(Duration, f64, f64, f64),
+    Done(Metrics),
 }
 
+/// 练习结束后的各项指标，词按每 5 个字符计算
+#[derive(Clone, Debug, PartialEq)]
+pub struct Metrics {
+    pub elapsed: Duration,
+    /// 净准确率
+    pub accuracy: f64,
+    /// 原始准确率
+    pub raw_accuracy: f64,
+    /// 净速度：原始速度减去每分钟未改正的错误数
+    pub wpm: f64,
+    /// 原始速度：每分钟输入的词数，包括错误和之后被删除的输入
+    pub raw_wpm: f64,
+    /// 每分钟留在文本中的正确字符数
+    pub cpm: f64,
+    /// 稳定性：1 减去每秒原始速度的变异系数，最低为 0
+    pub consistency: f64,
+    /// 字/分钟：每分钟完成的汉字数，文本中没有汉字时为 None
+    pub hanzi_per_minute: Option<f64>,
+    /// 每一秒的原始速度
+    pub speeds: Vec<f64>,
+    /// 每一秒的错误数
+    pub errors: Vec<usize>,
+}
+
+/// 一秒内的按键
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Second {
+    pub typed: usize,
+    pub errors: usize,
+}
 
+
+/// 练习的统计
+///
+/// 原始（raw）统计记录每一次按键，包括之后被删除的和在行尾被拒绝的输入；
+/// 净（net）统计只看当前留在文本里的输入。退格不算按键，只改变净统计。
 pub struct Evaluator {
+    /// 原始：输入的字符数
     pub total_chars_typed: usize,
+    /// 原始：其中错误的字符数
     pub total_char_errors: usize,
+    /// 净：文本中正确的字符数
     pub final_chars_typed_correctly: usize,
+    /// 净：文本中错误的字符数，即没有改正的错误
     pub final_uncorrected_errors: usize,
-    pub start_at: Instant,
-    pub pause_at: Instant,
+    /// 原始：每一秒输入的字符数和错误数
+    pub seconds: Vec<Second>,
+    /// 净：已经完成的汉字数
+    pub hanzi_completed: usize,
+    /// 文本中的汉字数，不随练习重置
+    pub hanzi_total: usize,
+}
+
+impl Default for Evaluator {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Evaluator {
@@ -21,8 +72,9 @@ impl Evaluator {
             total_char_errors: 0,
             final_chars_typed_correctly: 0,
             final_uncorrected_errors: 0,
-            start_at: Instant::now(),
-            pause_at: Instant::now(),
+            seconds: Vec::new(),
+            hanzi_completed: 0,
+            hanzi_total: 0,
         }
     }
     pub fn reset(&mut self) {
@@ -30,34 +82,158 @@ impl Evaluator {
         self.total_chars_typed = 0;
         self.final_chars_typed_correctly = 0;
         self.final_uncorrected_errors = 0;
+        self.seconds.clear();
+        self.hanzi_completed = 0;
+    }
+
+    /// 记录开始后 at 时刻的一次按键
+    pub fn record(&mut self, at: Duration, error: bool) {
+        let second = at.as_secs() as usize;
+        if self.seconds.len() <= second {
+            self.seconds.resize(second + 1, Second::default());
+        }
+        self.seconds[second].typed += 1;
+        if error {
+            self.seconds[second].errors += 1;
+        }
+    }
+
+    /// 已经改正的错误：被退格删除的，或者被拒绝而没有进入文本的
+    pub fn corrected_errors(&self) -> usize {
+        self.total_char_errors - self.final_uncorrected_errors
+    }
+
+    /// 原始准确率：正确的按键占所有按键的比例，没有输入时为 1
+    pub fn raw_accuracy(&self) -> f64 {
+        ratio(self.total_chars_typed - self.total_char_errors, self.total_chars_typed)
+    }
+
+    /// 净准确率：文本中正确的字符占已输入字符的比例，没有输入时为 1
+    pub fn net_accuracy(&self) -> f64 {
+        ratio(self.final_chars_typed_correctly, self.final_chars_typed_correctly + self.final_uncorrected_errors)
+    }
+
+    pub fn raw_wpm(&self, delta: Duration) -> f64 {
+        per_minute(self.total_chars_typed as f64 / 5.0, delta)
+    }
+
+    pub fn net_wpm(&self, delta: Duration) -> f64 {
+        (self.raw_wpm(delta) - per_minute(self.final_uncorrected_errors as f64, delta)).max(0.0)
+    }
+
+    pub fn cpm(&self, delta: Duration) -> f64 {
+        per_minute(self.final_chars_typed_correctly as f64, delta)
     }
-    pub fn accuracy(&self) -> f64 {
-        (self.total_chars_typed as isize - self.total_char_errors as isize) as f64
-        / self.total_chars_typed as f64
+
+    pub fn hanzi_per_minute(&self, delta: Duration) -> Option<f64> {
+        if self.hanzi_total == 0 {
+            None
+        } else {
+            Some(per_minute(self.hanzi_completed as f64, delta))
+        }
     }
 
-    pub fn real_time_accuracy(&self) -> f64 {
-        self.final_chars_typed_correctly  as f64
-        / (self.final_chars_typed_correctly + self.final_uncorrected_errors) as f64
+    /// 每一秒的按键，总用时取整到秒，最后不足一秒的按键并入最后一秒
+    pub fn timeline(&self, delta: Duration) -> Vec<Second> {
+        let len = (delta.as_secs_f64().round() as usize).max(1);
+        let mut timeline = vec![Second::default(); len];
+        for (idx, second) in self.seconds.iter().enumerate() {
+            let merged = &mut timeline[idx.min(len - 1)];
+            merged.typed += second.typed;
+            merged.errors += second.errors;
+        }
+        timeline
     }
 
-    pub fn real_time_wpm(&self, delta: Duration) -> f64 {
-        (self.final_chars_typed_correctly as f64 / 5.0 - self.final_uncorrected_errors as f64)
-        .max(0.0) as f64
-        / (delta.as_secs_f64() / 60.0)
+    /// 每一秒的原始速度
+    pub fn speeds(&self, delta: Duration) -> Vec<f64> {
+        self.timeline(delta).iter().map(|second| second.typed as f64 / 5.0 * 60.0).collect()
     }
 
-    pub fn wpm(&self, delta: Duration) -> f64 {
-        (self.total_chars_typed as f64 / 5.0 - self.total_char_errors as f64)
-        .max(0.0) as f64
-        / (delta.as_secs_f64() / 60.0)
+    pub fn consistency(&self, delta: Duration) -> f64 {
+        let speeds = self.speeds(delta);
+        let mean = speeds.iter().sum::<f64>() / speeds.len() as f64;
+        if mean == 0.0 {
+            return 0.0;
+        }
+        let variance = speeds.iter().map(|speed| (speed - mean).powi(2)).sum::<f64>() / speeds.len() as f64;
+        (1.0 - variance.sqrt() / mean).max(0.0)
     }
 
     pub fn snap(&self, delta: Duration) -> EvalResult {
-        EvalResult::Snap(self.real_time_accuracy(), self.real_time_wpm(delta))
+        EvalResult::Snap(self.net_accuracy(), self.net_wpm(delta))
     }
 
     pub fn done(&self, delta: Duration) -> EvalResult {
-        EvalResult::Done(delta, self.real_time_accuracy(), self.accuracy(), self.wpm(delta))
+        EvalResult::Done(Metrics {
+            elapsed: delta,
+            accuracy: self.net_accuracy(),
+            raw_accuracy: self.raw_accuracy(),
+            wpm: self.net_wpm(delta),
+            raw_wpm: self.raw_wpm(delta),
+            cpm: self.cpm(delta),
+            consistency: self.consistency(delta),
+            hanzi_per_minute: self.hanzi_per_minute(delta),
+            speeds: self.speeds(delta),
+            errors: self.timeline(delta).iter().map(|second| second.errors).collect(),
+        })
+    }
+}
+
+fn per_minute(count: f64, delta: Duration) -> f64 {
+    if delta.is_zero() {
+        0.0
+    } else {
+        count / (delta.as_secs_f64() / 60.0)
+    }
+}
+
+fn ratio(part: usize, total: usize) -> f64 {
+    if total == 0 {
+        1.0
+    } else {
+        part as f64 / total as f64
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_metrics() {
+        let mut evaluator = Evaluator::new();
+        evaluator.total_chars_typed = 60;
+        evaluator.total_char_errors = 6;
+        evaluator.final_chars_typed_correctly = 50;
+        evaluator.final_uncorrected_errors = 2;
+        let delta = Duration::from_secs(30);
+        assert_eq!(evaluator.raw_wpm(delta), 24.0);
+        assert_eq!(evaluator.net_wpm(delta), 20.0);
+        assert_eq!(evaluator.cpm(delta), 100.0);
+        assert_eq!(evaluator.corrected_errors(), 4);
+        assert_eq!(evaluator.raw_accuracy(), 0.9);
+        assert_eq!(evaluator.net_wpm(Duration::ZERO), 0.0);
+        assert_eq!(evaluator.hanzi_per_minute(delta), None);
+        evaluator.hanzi_total = 20;
+        evaluator.hanzi_completed = 15;
+        assert_eq!(evaluator.hanzi_per_minute(delta), Some(30.0));
+    }
+
+    #[test]
+    fn test_consistency() {
+        let mut evaluator = Evaluator::new();
+        for ms in [0, 500, 1000, 1500, 2000, 2500, 3100] {
+            evaluator.record(Duration::from_millis(ms), ms == 1500);
+        }
+        let seconds: Vec<_> = evaluator.seconds.iter().map(|second| (second.typed, second.errors)).collect();
+        assert_eq!(seconds, vec![(2, 0), (2, 1), (2, 0), (1, 0)]);
+        // 3.1 秒取整为 3 秒，最后一次按键并入第 3 秒
+        let delta = Duration::from_millis(3100);
+        assert_eq!(evaluator.speeds(delta), vec![24.0, 24.0, 36.0]);
+        let consistency = evaluator.consistency(delta);
+        assert!((consistency - (1.0 - 32f64.sqrt() / 28.0)).abs() < 1e-9);
+        evaluator.seconds = vec![Second { typed: 3, errors: 0 }; 3];
+        assert_eq!(evaluator.consistency(delta), 1.0);
     }
 }
@@ -0,0 +1,85 @@
+use std::time::Duration;
+use anyhow::Result;
+use crossterm::event;
+#[cfg(test)]
+use {std::collections::VecDeque, crossterm::event::{KeyCode, KeyEvent, KeyModifiers}};
+
+pub enum Event {
+    /// 按键、窗口大小变化等终端事件
+    Term(event::Event),
+    /// 一段时间内没有终端事件，用来刷新计时
+    Tick,
+}
+
+/// 输入事件的来源，没有更多事件时返回 None
+pub trait EventSource {
+    fn read(&mut self) -> Result<Option<Event>>;
+}
+
+/// 从终端读取事件，每隔 tick 没有事件时返回 `Event::Tick`
+pub struct CrosstermEvents {
+    tick: Duration,
+}
+
+impl CrosstermEvents {
+    pub fn new(tick: Duration) -> Self {
+        Self { tick }
+    }
+}
+
+impl EventSource for CrosstermEvents {
+    fn read(&mut self) -> Result<Option<Event>> {
+        if event::poll(self.tick)? {
+            Ok(Some(Event::Term(event::read()?)))
+        } else {
+            Ok(Some(Event::Tick))
+        }
+    }
+}
+
+/// 按顺序返回预先写好的事件，用于测试
+#[cfg(test)]
+pub struct ScriptedEvents {
+    events: VecDeque<Event>,
+}
+
+#[cfg(test)]
+impl ScriptedEvents {
+    pub fn new() -> Self {
+        Self { events: VecDeque::new() }
+    }
+
+    /// 逐个输入字符，`\n` 为回车，`\x08` 为退格
+    pub fn keys(mut self, s: &str) -> Self {
+        for ch in s.chars() {
+            let code = match ch {
+                '\n' => KeyCode::Enter,
+                '\x08' => KeyCode::Backspace,
+                ch => KeyCode::Char(ch),
+            };
+            self = self.key(code, KeyModifiers::NONE);
+        }
+        self
+    }
+
+    pub fn key(self, code: KeyCode, modifiers: KeyModifiers) -> Self {
+        self.event(event::Event::Key(KeyEvent::new(code, modifiers)))
+    }
+
+    pub fn event(mut self, event: event::Event) -> Self {
+        self.events.push_back(Event::Term(event));
+        self
+    }
+
+    pub fn tick(mut self) -> Self {
+        self.events.push_back(Event::Tick);
+        self
+    }
+}
+
+#[cfg(test)]
+impl EventSource for ScriptedEvents {
+    fn read(&mut self) -> Result<Option<Event>> {
+        Ok(self.events.pop_front())
+    }
+}
@@ -0,0 +1,223 @@
+use std::{fmt, str::FromStr};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::Deserialize;
+
+/// 可以绑定按键的操作
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Action {
+    Restart,
+    Next,
+    Quit,
+    /// 暂停或继续计时
+    Pause,
+}
+
+impl Action {
+    /// 页脚中显示的说明
+    fn describe(&self) -> &'static str {
+        match self {
+            Action::Restart => "restart",
+            Action::Next => "next",
+            Action::Quit => "quit",
+            Action::Pause => "pause",
+        }
+    }
+}
+
+/// 一个按键组合，写作 `ctrl-r`、`alt-n`、`esc`、`f5` 等
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct Key {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl Key {
+    pub fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        Self { code, modifiers }
+    }
+
+    pub fn matches(&self, event: &KeyEvent) -> bool {
+        self.code == event.code && self.modifiers == event.modifiers
+    }
+}
+
+impl FromStr for Key {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts: Vec<&str> = s.split('-').collect();
+        // `ctrl--` 绑定的是减号
+        if s.ends_with("--") {
+            parts.pop();
+            parts.pop();
+            parts.push("-");
+        }
+        let name = parts.pop().unwrap_or_default();
+        let mut modifiers = KeyModifiers::NONE;
+        for part in parts {
+            modifiers |= match part.to_lowercase().as_str() {
+                "ctrl" => KeyModifiers::CONTROL,
+                "alt" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                _ => return Err(anyhow::Error::msg(format!("未知的修饰键: {}", s))),
+            };
+        }
+        // 单个字符区分大小写，例如 ctrl-shift-R，按键名不区分
+        if name.chars().count() == 1 {
+            let code = KeyCode::Char(name.chars().next().unwrap());
+            // 不带 ctrl、alt 的字符需要用来输入
+            if !modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) {
+                return Err(anyhow::Error::msg(format!("按键绑定与输入冲突: {}", s)));
+            }
+            return Ok(Key::new(code, modifiers));
+        }
+        let code = match name.to_lowercase().as_str() {
+            "esc" => KeyCode::Esc,
+            "tab" => KeyCode::Tab,
+            "delete" | "del" => KeyCode::Delete,
+            "insert" | "ins" => KeyCode::Insert,
+            "home" => KeyCode::Home,
+            "end" => KeyCode::End,
+            "pageup" => KeyCode::PageUp,
+            "pagedown" => KeyCode::PageDown,
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            name => match name.strip_prefix('f').and_then(|n| n.parse::<u8>().ok()) {
+                Some(n) if (1..=12).contains(&n) => KeyCode::F(n),
+                _ => return Err(anyhow::Error::msg(format!("未知的按键: {}", s))),
+            },
+        };
+        Ok(Key::new(code, modifiers))
+    }
+}
+
+impl TryFrom<String> for Key {
+    type Error = anyhow::Error;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (modifier, name) in [(KeyModifiers::CONTROL, "ctrl-"), (KeyModifiers::ALT, "alt-"), (KeyModifiers::SHIFT, "shift-")] {
+            if self.modifiers.contains(modifier) {
+                f.write_str(name)?;
+            }
+        }
+        match self.code {
+            KeyCode::Char(ch) => write!(f, "{}", ch),
+            KeyCode::F(n) => write!(f, "F{}", n),
+            KeyCode::Esc => f.write_str("ESC"),
+            code => write!(f, "{:?}", code),
+        }
+    }
+}
+
+/// 按键绑定
+///
+/// ```toml
+/// [keys]
+/// restart = "ctrl-r"
+/// next = "ctrl-n"
+/// quit = "esc"
+/// pause = "ctrl-p"
+/// ```
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct KeyBindings {
+    pub restart: Key,
+    pub next: Key,
+    pub quit: Key,
+    pub pause: Key,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self {
+            restart: Key::new(KeyCode::Char('r'), KeyModifiers::CONTROL),
+            next: Key::new(KeyCode::Char('n'), KeyModifiers::CONTROL),
+            quit: Key::new(KeyCode::Esc, KeyModifiers::NONE),
+            pause: Key::new(KeyCode::Char('p'), KeyModifiers::CONTROL),
+        }
+    }
+}
+
+impl KeyBindings {
+    /// 按页脚中的顺序排列，窗口太窄时排在后面的不显示
+    fn bindings(&self) -> [(Action, Key); 4] {
+        [(Action::Restart, self.restart), (Action::Next, self.next), (Action::Quit, self.quit), (Action::Pause, self.pause)]
+    }
+
+    /// 检查同一个按键没有绑定多个操作，否则排在后面的操作永远不会触发
+    pub fn validate(&self) -> anyhow::Result<()> {
+        let bindings = self.bindings();
+        for (idx, (action, key)) in bindings.iter().enumerate() {
+            if let Some((other, _)) = bindings[..idx].iter().find(|(_, other)| other == key) {
+                return Err(anyhow::Error::msg(format!("按键 {} 同时绑定了 {} 和 {}", key, other.describe(), action.describe())));
+            }
+        }
+        Ok(())
+    }
+
+    /// 按键对应的操作
+    pub fn action(&self, event: &KeyEvent) -> Option<Action> {
+        self.bindings()
+            .into_iter()
+            .find(|(_, key)| key.matches(event))
+            .map(|(action, _)| action)
+    }
+
+    /// 页脚中显示的 (按键, 说明)
+    pub fn footer(&self) -> Vec<(String, &'static str)> {
+        self.bindings()
+            .into_iter()
+            .map(|(action, key)| (key.to_string(), action.describe()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_key() {
+        assert_eq!("ctrl-r".parse::<Key>().unwrap(), Key::new(KeyCode::Char('r'), KeyModifiers::CONTROL));
+        assert_eq!("Esc".parse::<Key>().unwrap(), Key::new(KeyCode::Esc, KeyModifiers::NONE));
+        assert_eq!("alt-shift-f5".parse::<Key>().unwrap(), Key::new(KeyCode::F(5), KeyModifiers::ALT | KeyModifiers::SHIFT));
+        assert_eq!("ctrl--".parse::<Key>().unwrap(), Key::new(KeyCode::Char('-'), KeyModifiers::CONTROL));
+        assert!("q".parse::<Key>().is_err());
+        assert!("hyper-q".parse::<Key>().is_err());
+        assert!("ctrl-foo".parse::<Key>().is_err());
+        assert!("c-r".parse::<Key>().is_err());
+        assert_eq!("CTRL-Shift-R".parse::<Key>().unwrap(), Key::new(KeyCode::Char('R'), KeyModifiers::CONTROL | KeyModifiers::SHIFT));
+        assert_eq!("ctrl-shift-R".parse::<Key>().unwrap().to_string(), "ctrl-shift-R");
+        assert_eq!("ctrl-r".parse::<Key>().unwrap().to_string(), "ctrl-r");
+        assert_eq!("esc".parse::<Key>().unwrap().to_string(), "ESC");
+    }
+
+    #[test]
+    fn test_bindings() {
+        let keys = KeyBindings { quit: "ctrl-q".parse().unwrap(), ..Default::default() };
+        let event = KeyEvent::new(KeyCode::Char('q'), KeyModifiers::CONTROL);
+        assert_eq!(keys.action(&event), Some(Action::Quit));
+        assert_eq!(keys.action(&KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE)), None);
+        assert_eq!(keys.action(&KeyEvent::new(KeyCode::Char('r'), KeyModifiers::CONTROL)), Some(Action::Restart));
+        assert_eq!(keys.footer()[2], (String::from("ctrl-q"), "quit"));
+        assert_eq!(keys.action(&KeyEvent::new(KeyCode::Char('p'), KeyModifiers::CONTROL)), Some(Action::Pause));
+        assert!(keys.validate().is_ok());
+    }
+
+    #[test]
+    fn test_duplicate() {
+        let keys = KeyBindings { restart: "esc".parse().unwrap(), ..Default::default() };
+        assert_eq!(keys.validate().unwrap_err().to_string(), "按键 ESC 同时绑定了 restart 和 quit");
+        let keys = KeyBindings { pause: "ctrl-shift-R".parse().unwrap(), ..Default::default() };
+        assert!(keys.validate().is_ok());
+    }
+}
@@ -0,0 +1,8 @@
+//! 打字练习的核心逻辑，与终端界面无关
+
+pub mod utils;
+pub mod textgen;
+pub mod evaluator;
+pub mod corpus;
+pub mod session;
+pub mod page;
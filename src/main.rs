@@ -1,147 +1,425 @@
-#![feature(once_cell)]
-mod utils;
-mod textgen;
 mod tui;
-mod evaluator;
-use std::{io::BufReader, fs::File, time::Instant};
+mod backend;
+mod events;
+mod config;
+mod sound;
+mod keys;
+mod theme;
+mod chart;
+use std::time::{Duration, Instant};
 
 use crossterm::event;
-use rodio::{OutputStream, Decoder, Source, Sink};
 use anyhow::Result;
+use typlap::{corpus, textgen, session::{Change, Input, Session}};
+use backend::Backend;
+use events::{Event, EventSource};
 
-pub struct Typlap {
-    pub ui: tui::Tui,
-    pub evaluator: evaluator::Evaluator,
-    pub started: bool,
-    pub done: bool,
-    // stream_handle: OutputStreamHandle,
+/// 没有输入时刷新顶部统计和计时的间隔
+const TICK: Duration = Duration::from_millis(200);
+
+pub struct Typlap<B: Backend, E: EventSource> {
+    pub ui: tui::Tui<B>,
+    pub session: Session,
+    pub sound: Option<sound::Sound>,
+    pub keys: keys::KeyBindings,
+    pub events: E,
+    idle_pause: Option<Duration>,
+    time_limit: Option<Duration>,
 }
 
-impl Typlap {
-    pub fn new(file_path: &str) -> Self {
+impl<B: Backend, E: EventSource> Typlap<B, E> {
+    pub fn new(content: &str, config: &config::Config, ascii_fallback: bool, sound: Option<sound::Sound>, backend: B, events: E) -> Self {
         let mut tg = textgen::TextGenerator::new();
-        tg.read_content(file_path).unwrap();
+        tg.load_content(content);
         let iter = tg.into_iter();
+        let mut ui = tui::Tui::new(iter, backend).unwrap();
+        ui.set_scheme(config.scheme);
+        ui.set_ascii_fallback(ascii_fallback);
+        ui.set_length(config.length);
+        ui.set_timed(config.time.is_some());
+        ui.set_cursor_shape(config.cursor.into());
+        ui.set_footer(config.keys.footer());
+        ui.set_theme(config.theme.into());
         Self { 
-            ui: tui::Tui::new(iter).unwrap(),
-            evaluator: evaluator::Evaluator::new(),
-            started: false,
-            done: false,
+            ui,
+            session: Session::new(Vec::new()),
+            sound,
+            keys: config.keys.clone(),
+            events,
+            idle_pause: config.idle_pause(),
+            time_limit: config.time.map(Duration::from_secs),
         }
     }
 
+    /// 生成新的文本，开始新一轮练习
+    fn next_text(&mut self) -> Result<()> {
+        self.ui.init()?;
+        self.session = Session::new(self.ui.text.lines.raw_text.clone());
+        self.session.set_hanzi(self.ui.text.lines.hanzi.clone());
+        self.session.set_idle_pause(self.idle_pause);
+        self.session.set_time_limit(self.time_limit);
+        Ok(())
+    }
 
     pub fn typing(&mut self) -> Result<()>{
-        self.ui.init()?;
-        let file = BufReader::new(File::open("./bee.wav").unwrap());
-        let (_stream, stream_handle) = OutputStream::try_default().unwrap();
-        let source = Decoder::new(file).unwrap();
-        let source_buf = source.buffered();
-        let mut start_at = Instant::now();
-        loop {
-            match event::read()? {
-                event::Event::Key(event) => {
-                    if !self.started {
-                        self.evaluator.reset();
-                        self.started = true;
-                        start_at = Instant::now();
+        self.next_text()?;
+        while let Some(event) = self.events.read()? {
+            if self.session.time_up(Instant::now()) {
+                self.finish()?;
+            }
+            match event {
+                Event::Tick => {}
+                Event::Term(event::Event::Key(event)) => {
+                    let action = self.keys.action(&event);
+                    // 窗口太小时只响应退出
+                    if self.ui.too_small() && action != Some(keys::Action::Quit) {
+                        continue
                     }
-                    let sink = Sink::try_new(&stream_handle).unwrap();
-                    sink.append(source_buf.clone());
-                    sink.detach();
-                    match (event.code, event.modifiers) {
-                        (event::KeyCode::Char(mut ch), event::KeyModifiers::NONE | event::KeyModifiers::SHIFT)=> {
-                            if self.done {
-                                continue
-                            }
-                            if event.modifiers == event::KeyModifiers::SHIFT {
-                                ch = ch.to_ascii_uppercase()
-                            }
-                            match self.ui.display_c(&ch) {
-                                Ok(correct) => {
-                                    if correct {
-                                        self.evaluator.final_chars_typed_correctly += 1;
-                                    } else {
-                                        self.evaluator.total_char_errors += 1;
-                                        self.evaluator.final_uncorrected_errors += 1;
-                                    }
-                                    self.evaluator.total_chars_typed += 1;
-                                },
-                                Err(e) => return Err(e)
-                            }
-                        }
-
-                        (event::KeyCode::Backspace, event::KeyModifiers::NONE) => {
-                            if self.done {
-                                continue
-                            }
-                            match self.ui.display_with_backspace() {
-                                Ok(res) => {
-                                    if res == -1 {
-                                        self.evaluator.final_chars_typed_correctly -= 1;
-                                    } else if res == 1 {
-                                        self.evaluator.final_uncorrected_errors -= 1;
-                                    }
-                                },
-                                Err(e) => return Err(e),
-                            }
+                    match action {
+                        Some(keys::Action::Next) => {
+                            self.next_text()?;
+                            continue
                         }
-
-                        (event::KeyCode::Enter, event::KeyModifiers::NONE) => {
-                            if self.done {
-                                continue
-                            }
-                            if let Ok((done, correct)) = self.ui.move_next_line() {
-                                if correct {
-                                    self.evaluator.final_chars_typed_correctly += 1;
-                                } else {
-                                    self.evaluator.final_uncorrected_errors += 1;
-                                }
-                                self.evaluator.total_chars_typed += 1;
-                                if done == true {
-                                    let end_at = std::time::Instant::now();
-                                    self.ui.display_result(self.evaluator.done(end_at.duration_since(start_at)))?;
-                                    self.started = false;
-                                    self.done = done;
-                                }
-                            }
+                        Some(keys::Action::Restart) => {
+                            self.session.reset();
+                            self.ui.restart()?;
+                            continue
                         }
-                        
-                        (event::KeyCode::Char('n'), event::KeyModifiers::CONTROL) => {
-                            self.evaluator.reset();
-                            self.started = false;
-                            self.done = false;
-                            self.ui.init()?;
+                        Some(keys::Action::Quit) => break,
+                        Some(keys::Action::Pause) => {
+                            self.session.toggle_pause(Instant::now());
+                            self.display_header()?;
+                            continue
                         }
-
-                        (event::KeyCode::Char('r'), event::KeyModifiers::CONTROL) => {
-                            self.evaluator.reset();
-                            self.started = false;
-                            self.done = false;
-                            self.ui.reset()?;
+                        None => {}
+                    }
+                    let input = match (event.code, event.modifiers) {
+                        (event::KeyCode::Char(mut ch), event::KeyModifiers::NONE | event::KeyModifiers::SHIFT) => {
+                            if event.modifiers == event::KeyModifiers::SHIFT {
+                                ch = ch.to_ascii_uppercase()
+                            }
+                            Input::Char(ch)
                         }
-
-                        (event::KeyCode::Esc, event::KeyModifiers::NONE) => {
-                            break;
-                        } 
-                        _ => {}
+                        (event::KeyCode::Backspace, event::KeyModifiers::NONE) => Input::Backspace,
+                        (event::KeyCode::Enter, event::KeyModifiers::NONE) => Input::Enter,
+                        _ => continue,
+                    };
+                    let change = self.session.handle(input, Instant::now());
+                    self.display(change)?;
+                }
+                Event::Term(event::Event::Resize(cols, rows)) => {
+                    self.ui.set_size(cols, rows);
+                    if let Some(result) = self.session.result() {
+                        self.ui.display_result(result)?;
+                    } else if !self.ui.has_text() {
+                        self.next_text()?;
+                    } else {
+                        self.ui.resize(&mut self.session)?;
                     }
                 }
                 _ => {}
             }
-            if self.started {
-                let end_at = std::time::Instant::now();
-                self.ui.display_result(self.evaluator.snap(end_at.duration_since(start_at)))?;
+            self.display_header()?;
+        }
+        Ok(())
+    }
+
+    /// 在顶部显示实时统计和计时，暂停时显示提示
+    fn display_header(&mut self) -> Result<()> {
+        let now = Instant::now();
+        if self.session.is_done() {
+            return Ok(());
+        }
+        if self.session.is_paused(now) {
+            self.ui.display_paused()?;
+        } else if let Some(snap) = self.session.snap(now) {
+            self.ui.display_result(snap)?;
+        }
+        // 限时练习一直显示剩余时间，否则开始后显示用时
+        match self.session.remaining(now) {
+            Some(remaining) => self.ui.display_timer(&format!("{}s left", remaining.as_secs_f64().ceil()))?,
+            None if self.session.is_started() => self.ui.display_timer(&format!("{}s", self.session.active(now).as_secs()))?,
+            None => {}
+        }
+        Ok(())
+    }
+
+    /// 按输入造成的变化重绘界面并播放按键音
+    fn display(&mut self, change: Change) -> Result<()> {
+        match change {
+            Change::Typed { line, idx, ch, correct } => {
+                self.play(match (correct, ch) {
+                    (true, ' ') => sound::Effect::Space,
+                    (true, '↵') => sound::Effect::Enter,
+                    (true, _) => sound::Effect::Correct,
+                    (false, _) => sound::Effect::Error,
+                });
+                self.ui.display_typed(line, idx, ch)?;
+            }
+            Change::Erased { line, idx } => {
+                self.play(sound::Effect::Correct);
+                self.ui.display_erased(line, idx)?;
             }
+            Change::Rejected => self.play(sound::Effect::Error),
+            Change::Ignored => return Ok(()),
+        }
+        if self.session.is_done() {
+            if !self.ui.next_page()? {
+                return self.finish();
+            }
+            self.session.next_page(self.ui.text.lines.raw_text.clone(), self.ui.text.lines.hanzi.clone());
+        }
+        let (line, idx) = self.session.cursor();
+        self.ui.move_cursor(line, idx)
+    }
+
+    /// 显示结果并播放完成音
+    fn finish(&mut self) -> Result<()> {
+        if let Some(result) = self.session.result() {
+            self.ui.display_result(result)?;
+            self.play(sound::Effect::Finish);
         }
         Ok(())
     }
+
+    fn play(&self, effect: sound::Effect) {
+        if let Some(sound) = &self.sound {
+            sound.play(effect);
+        }
+    }
 }
 
 
 
 fn main() -> Result<()> {
-    let mut t = Typlap::new("./text/it.txt");
-    t.typing().unwrap();
+    let mut config = config::Config::load()?;
+    typlap::utils::util::init_punctuation(&config.punctuation.table, config.punctuation.full_width);
+
+    let mut ascii_fallback = false;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-s" | "--scheme" => {
+                config.scheme = args.next().ok_or(anyhow::Error::msg("--scheme 缺少参数"))?.parse()?;
+            }
+            "-t" | "--time" => {
+                let time = args.next().ok_or(anyhow::Error::msg("--time 缺少参数"))?;
+                config.time = Some(time.parse().map_err(|_| anyhow::Error::msg(format!("无效的秒数: {}", time)))?);
+            }
+            "--ascii" => ascii_fallback = true,
+            "--no-sound" => config.sound.enabled = false,
+            "--list" => {
+                for (name, origin) in corpus::list() {
+                    match origin {
+                        corpus::Origin::Builtin => println!("{}", name),
+                        corpus::Origin::User(path) => println!("{}\t{}", name, path.display()),
+                    }
+                }
+                return Ok(());
+            }
+            _ => config.corpus = arg,
+        }
+    }
+    let content = corpus::load(&config.corpus)?;
+    // 没有音频设备时不播放按键音
+    let sound = if config.sound.enabled {
+        sound::Sound::new(config.sound_pack().as_deref(), config.sound.volume, config.sound.pitch)?
+    } else {
+        None
+    };
+    let skipped = {
+        let mut t = Typlap::new(&content, &config, ascii_fallback, sound, backend::CrosstermBackend::new(), events::CrosstermEvents::new(TICK));
+        t.typing().unwrap();
+        t.ui.text.skipped.clone()
+    };
+    // 界面已经退出，列出当前输入方案下无法转换的词
+    if !skipped.is_empty() {
+        eprintln!("跳过了 {} 个无法转换的词: {}", skipped.len(), skipped.join(" "));
+    }
     Ok(())
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crossterm::event::{KeyCode, KeyModifiers};
+    use typlap::evaluator::EvalResult;
+    use backend::BufferBackend;
+    use events::ScriptedEvents;
+
+    /// 50 列宽的窗口每行放两个词，练习三个词：`WORD WORD↵`、`WORD↵`
+    const WORD: &str = "abcdefghijklm";
+    const PERFECT: &str = "abcdefghijklm abcdefghijklm\nabcdefghijklm\n";
+
+    fn run(events: ScriptedEvents) -> Typlap<BufferBackend, ScriptedEvents> {
+        run_content(&[WORD; 10].join(" "), events)
+    }
+
+    fn run_content(content: &str, events: ScriptedEvents) -> Typlap<BufferBackend, ScriptedEvents> {
+        run_config(content, config::Config { length: Some(3), ..Default::default() }, events)
+    }
+
+    fn run_config(content: &str, config: config::Config, events: ScriptedEvents) -> Typlap<BufferBackend, ScriptedEvents> {
+        let mut t = Typlap::new(content, &config, false, None, BufferBackend::new(50, 24), events);
+        t.typing().unwrap();
+        t
+    }
+
+    /// (净准确率, 原始准确率)
+    fn accuracy(t: &Typlap<BufferBackend, ScriptedEvents>) -> (f64, f64) {
+        match t.session.result() {
+            Some(EvalResult::Done(metrics)) => (metrics.accuracy, metrics.raw_accuracy),
+            _ => panic!("练习没有结束"),
+        }
+    }
+
+    #[test]
+    fn test_perfect() {
+        let t = run(ScriptedEvents::new().keys(PERFECT));
+        assert_eq!(t.session.lines, vec![format!("{} {}↵", WORD, WORD).chars().collect::<Vec<_>>(), format!("{}↵", WORD).chars().collect()]);
+        assert_eq!(accuracy(&t), (1.0, 1.0));
+        assert_eq!(t.session.evaluator.total_chars_typed, 42);
+        let screen = t.ui.backend.text();
+        assert!(screen.contains("Accuracy: 100.0%"));
+        assert!(screen.contains("Raw Accuracy: 100.0%"));
+        assert!(screen.contains("Consistency: "));
+        assert!(screen.contains("Speed per second"));
+    }
+
+    #[test]
+    fn test_error_marks() {
+        let t = run(ScriptedEvents::new().keys("x\x08").keys(PERFECT));
+        match t.session.result() {
+            Some(EvalResult::Done(metrics)) => assert_eq!(metrics.errors.iter().sum::<usize>(), 1),
+            _ => panic!("练习没有结束"),
+        }
+        // 整个练习都在第一秒内，整行都被标出
+        let marks = "x".repeat(40);
+        assert!(t.ui.backend.text().lines().any(|line| line.trim_matches(|ch| ch == '|' || ch == ' ') == marks));
+    }
+
+    #[test]
+    fn test_hanzi() {
+        let t = run_content(&["城市"; 10].join(" "), ScriptedEvents::new().keys("chengshi chengshi\nchengshi\n"));
+        assert_eq!(t.session.evaluator.hanzi_total, 6);
+        assert_eq!(t.session.evaluator.hanzi_completed, 6);
+        assert!(t.ui.backend.text().contains("字/分钟"));
+    }
+
+    #[test]
+    fn test_backspace() {
+        let t = run(ScriptedEvents::new().keys("x\x08").keys(PERFECT));
+        let evaluator = &t.session.evaluator;
+        assert_eq!((evaluator.total_chars_typed, evaluator.total_char_errors), (43, 1));
+        assert_eq!((evaluator.final_chars_typed_correctly, evaluator.final_uncorrected_errors), (42, 0));
+        assert_eq!(accuracy(&t), (1.0, 42.0 / 43.0));
+    }
+
+    #[test]
+    fn test_backspace_across_lines() {
+        let (first, second) = PERFECT.split_at(28);
+        let t = run(ScriptedEvents::new().keys(first).keys("\x08\x08m\n").keys(second));
+        let evaluator = &t.session.evaluator;
+        assert_eq!((evaluator.total_chars_typed, evaluator.total_char_errors), (44, 0));
+        assert_eq!(evaluator.final_chars_typed_correctly, 42);
+        assert_eq!(accuracy(&t), (1.0, 1.0));
+    }
+
+    #[test]
+    fn test_enter_mid_line() {
+        let t = run(ScriptedEvents::new().keys("abc\n\x08").keys(&PERFECT[3..]));
+        let evaluator = &t.session.evaluator;
+        assert_eq!((evaluator.total_chars_typed, evaluator.total_char_errors), (43, 1));
+        assert_eq!((evaluator.final_chars_typed_correctly, evaluator.final_uncorrected_errors), (42, 0));
+        assert_eq!(evaluator.corrected_errors(), 1);
+    }
+
+    #[test]
+    fn test_pages() {
+        // 8 行高的窗口只有两行文本，每页四个词，六个词分两页
+        let config = config::Config { length: Some(6), ..Default::default() };
+        let line = format!("{} {}\n", WORD, WORD);
+        let events = ScriptedEvents::new().keys(&line.repeat(3));
+        let mut t = Typlap::new(&[WORD; 10].join(" "), &config, false, None, BufferBackend::new(50, 8), events);
+        t.typing().unwrap();
+        assert_eq!(t.session.lines, vec![format!("{} {}↵", WORD, WORD).chars().collect::<Vec<_>>()]);
+        assert_eq!(t.session.evaluator.final_chars_typed_correctly, 84);
+        assert_eq!(accuracy(&t), (1.0, 1.0));
+        assert!(t.ui.backend.text().contains("for 84 words"));
+    }
+
+    #[test]
+    fn test_restart() {
+        let events = ScriptedEvents::new()
+            .keys("xyz")
+            .key(KeyCode::Char('r'), KeyModifiers::CONTROL)
+            .keys(PERFECT);
+        let t = run(events);
+        assert_eq!(accuracy(&t), (1.0, 1.0));
+        assert_eq!(t.session.evaluator.total_chars_typed, 42);
+    }
+
+    #[test]
+    fn test_pause() {
+        let ctrl_p = |events: ScriptedEvents| events.key(KeyCode::Char('p'), KeyModifiers::CONTROL);
+        let t = run(ctrl_p(ScriptedEvents::new().keys("abc")));
+        assert!(t.session.is_paused(Instant::now()));
+        assert!(t.ui.backend.line(1).contains("paused, type to resume"));
+        // 50 列放不下暂停键的说明
+        let footer = t.ui.backend.line(23);
+        assert!(footer.contains("ESC to quit") && !footer.contains("pause"));
+
+        let t = run(ctrl_p(ctrl_p(ScriptedEvents::new().keys("abc"))).keys("d"));
+        assert!(!t.session.is_paused(Instant::now()));
+        assert!(t.ui.backend.line(1).contains("current Accuracy: 100.0%"));
+        assert_eq!(t.session.cursor(), (0, 4));
+    }
+
+    #[test]
+    fn test_tick() {
+        let t = run(ScriptedEvents::new().keys("abc").tick());
+        assert!(t.ui.backend.line(0).contains(" 0s "));
+        assert!(t.ui.backend.line(1).contains("current Accuracy: 100.0%"));
+
+        let content = [WORD; 10].join(" ");
+        let config = config::Config { length: Some(3), time: Some(30), ..Default::default() };
+        let t = run_config(&content, config, ScriptedEvents::new().tick());
+        assert!(t.ui.backend.line(0).contains(" 30s left "));
+        assert!(!t.session.is_started());
+    }
+
+    #[test]
+    fn test_time_up() {
+        let content = [WORD; 10].join(" ");
+        let config = config::Config { length: Some(3), time: Some(0), ..Default::default() };
+        let t = run_config(&content, config, ScriptedEvents::new().keys("ab").tick().keys("c"));
+        assert_eq!(t.session.evaluator.total_chars_typed, 1);
+        assert_eq!(accuracy(&t), (1.0, 1.0));
+        assert!(t.ui.backend.text().contains("Tooks 0s"));
+    }
+
+    #[test]
+    fn test_timed_pages() {
+        // 限时练习不受 length 限制，输入完一页接着显示下一页
+        let config = config::Config { length: Some(3), time: Some(30), ..Default::default() };
+        let line = format!("{} {}\n", WORD, WORD);
+        let events = ScriptedEvents::new().keys(&line.repeat(3));
+        let mut t = Typlap::new(&[WORD; 20].join(" "), &config, false, None, BufferBackend::new(50, 8), events);
+        t.typing().unwrap();
+        assert!(!t.session.is_done());
+        assert_eq!(t.session.cursor(), (1, 0));
+        assert_eq!(t.session.evaluator.final_chars_typed_correctly, 84);
+        assert_eq!(t.ui.text.generated, 8);
+    }
+
+    #[test]
+    fn test_quit() {
+        let events = ScriptedEvents::new()
+            .keys("abc")
+            .key(KeyCode::Esc, KeyModifiers::NONE)
+            .keys(PERFECT);
+        let t = run(events);
+        assert!(t.session.result().is_none());
+        assert_eq!(t.session.cursor(), (0, 3));
+    }
+}
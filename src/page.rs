@@ -0,0 +1,166 @@
+//! 把转换好的词排成一页文本，得到显示和输入需要的各行，与终端无关
+use std::ops::Range;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+use crate::{session::Hanzi, utils::util::{self, Scheme, Segment}};
+
+/// 转换好的一个词，count 是其中的汉字数
+pub struct Word {
+    pub segments: Vec<Segment>,
+    pub count: usize,
+}
+
+impl Word {
+    /// 按输入方案转换词库中的一个词，无法转换时返回 None
+    pub fn new(word: &str, scheme: Scheme, ascii_fallback: bool) -> Option<Self> {
+        let mut segments = util::transform_word(word, scheme)?;
+        if ascii_fallback {
+            segments.iter_mut().for_each(|(_, target)| *target = util::to_ascii(target));
+        }
+        Some(Self { segments, count: util::count_chinese(word) })
+    }
+}
+
+/// 排好的各行，用来显示文本和创建 `Session`
+#[derive(Default)]
+pub struct Lines {
+    /// 每行需要输入的字符，最后一个是 `↵`
+    pub raw_text: Vec<Vec<char>>,
+    /// 每行的提示，和输入相同时为空
+    pub pinyin_text: Vec<String>,
+    /// 按显示列排好的输入行，字符之间可能有空隙
+    pub target_text: Vec<String>,
+    /// 输入行中每个字符相对行首所在的列
+    pub columns: Vec<Vec<u16>>,
+    /// 每行中对应汉字的输入段
+    pub hanzi: Vec<Vec<Hanzi>>,
+}
+
+impl Lines {
+    /// 把词排成不超过 max_cols 列的行，词之间用空格分开
+    pub fn build(words: &[Word], max_cols: usize) -> Self {
+        let mut lines = Self::default();
+        for range in wrap(words, max_cols) {
+            let mut segments = Vec::<Segment>::new();
+            let mut hanzi = Vec::new();
+            let mut len = 0;
+            for word in &words[range] {
+                if !segments.is_empty() {
+                    segments.push((String::from(" "), String::from(" ")));
+                    len += 1;
+                }
+                let word_len = word.segments.iter().map(|(_, target)| target.chars().count()).sum::<usize>();
+                if word.count > 0 {
+                    hanzi.push(Hanzi { start: len, end: len + word_len, count: word.count });
+                }
+                segments.extend(word.segments.iter().cloned());
+                len += word_len;
+            }
+            segments.push((String::from("↵"), String::from("↵")));
+
+            let (hint_line, line, columns) = layout(&segments);
+            if segments.iter().any(|(hint, target)| hint != target) {
+                lines.pinyin_text.push(hint_line);
+            } else {
+                lines.pinyin_text.push(String::new());
+            }
+            lines.raw_text.push(segments.iter().flat_map(|(_, target)| target.chars()).collect());
+            lines.target_text.push(line);
+            lines.columns.push(columns);
+            lines.hanzi.push(hanzi);
+        }
+        lines
+    }
+}
+
+/// 把词排成不超过 max_cols 列的行，返回每行的词的范围，每行至少有一个词
+pub fn wrap(words: &[Word], max_cols: usize) -> Vec<Range<usize>> {
+    let mut lines = Vec::new();
+    let mut start = 0;
+    let mut width = 0;
+    for (idx, word) in words.iter().enumerate() {
+        let word_width = word.segments.iter().map(cell_width).sum::<usize>();
+        if idx > start && width + word_width + 1 >= max_cols {
+            lines.push(start..idx);
+            start = idx;
+            width = 0;
+        }
+        width += word_width + 1;
+    }
+    lines.push(start..words.len());
+    lines
+}
+
+/// 一段所占的列数，提示和输入取较宽的一个
+fn cell_width((hint, target): &Segment) -> usize {
+    hint.width().max(target.width())
+}
+
+/// 按显示宽度排版一行，每段提示居中显示在对应的输入上方
+/// 返回 (提示行, 输入行, 输入行中每个字符相对行首所在的列)
+pub fn layout(segments: &[Segment]) -> (String, String, Vec<u16>) {
+    let mut hint_line = String::new();
+    let mut line = String::new();
+    let mut columns = Vec::new();
+    let mut col = 0;
+    for segment in segments {
+        let (hint, target) = segment;
+        let width = cell_width(segment);
+        let hint_pad = width - hint.width();
+        let target_pad = width - target.width();
+        hint_line.push_str(&" ".repeat(hint_pad / 2));
+        hint_line.push_str(hint);
+        hint_line.push_str(&" ".repeat(hint_pad - hint_pad / 2));
+
+        line.push_str(&" ".repeat(target_pad / 2));
+        let mut target_col = col + target_pad / 2;
+        for ch in target.chars() {
+            columns.push(target_col as u16);
+            target_col += ch.width().unwrap_or(0);
+        }
+        line.push_str(target);
+        line.push_str(&" ".repeat(target_pad - target_pad / 2));
+        col += width;
+    }
+    (hint_line, line.trim_end().to_string(), columns)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_layout() {
+        let segments = util::transform_word("长城，", Scheme::Pinyin).unwrap();
+        let (hint, line, columns) = layout(&segments);
+        assert_eq!(hint, " 长   城  ，");
+        assert_eq!(line, "zhangcheng,");
+        assert_eq!(columns, (0..11).collect::<Vec<u16>>());
+
+        // 注音比按键宽，按键之间留出空隙，每个音节最后是声调键
+        let segments = util::transform_word("中文", Scheme::Zhuyin).unwrap();
+        let (hint, line, columns) = layout(&segments);
+        assert_eq!(hint, "ㄓㄨㄥㄨㄣˊ");
+        assert_eq!(line, " 5j/   jp6");
+        assert_eq!(columns, vec![1, 2, 3, 4, 7, 8, 9]);
+    }
+
+    #[test]
+    fn test_build() {
+        let words: Vec<Word> = ["长城|changcheng", "rust", "重庆|chongqing"].iter()
+            .map(|word| Word::new(word, Scheme::Pinyin, false).unwrap())
+            .collect();
+        let lines = Lines::build(&words, 80);
+        assert_eq!(lines.raw_text, vec!["changcheng rust chongqing↵".chars().collect::<Vec<char>>()]);
+        assert_eq!(lines.hanzi[0], vec![
+            Hanzi { start: 0, end: 10, count: 2 },
+            Hanzi { start: 16, end: 25, count: 2 },
+        ]);
+
+        // 放不下时换行，没有提示的行提示为空
+        let lines = Lines::build(&words[1..2], 80);
+        assert_eq!(lines.pinyin_text, vec![String::new()]);
+        let lines = Lines::build(&words, 20);
+        assert_eq!(lines.raw_text.len(), 2);
+        assert_eq!(lines.target_text[1], "chongqing↵");
+    }
+}
@@ -0,0 +1,595 @@
+use std::time::{Duration, Instant};
+use crate::evaluator::{EvalResult, Evaluator};
+
+/// 交给会话处理的输入
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Input {
+    Char(char),
+    Backspace,
+    /// 换行，对应每行末尾的 `↵`
+    Enter,
+}
+
+/// 一次输入对文本造成的变化，界面据此重绘，之后光标移动到 `Session::cursor`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Change {
+    /// 在第 line 行第 idx 个位置输入了 ch
+    Typed { line: usize, idx: usize, ch: char, correct: bool },
+    /// 删除了第 line 行第 idx 个位置的输入
+    Erased { line: usize, idx: usize },
+    /// 行尾已经没有位置，输入记为错误
+    Rejected,
+    /// 练习已经结束或没有可以删除的输入，输入被忽略
+    Ignored,
+}
+
+/// 输入行中 `[start, end)` 的字符对应的原文，全部输入正确时完成 count 个汉字
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Hanzi {
+    pub start: usize,
+    pub end: usize,
+    pub count: usize,
+}
+
+/// 与界面无关的练习会话：需要输入的文本、每行已输入的字符、光标和统计
+pub struct Session {
+    /// 每行需要输入的字符，最后一个是 `↵`
+    pub lines: Vec<Vec<char>>,
+    /// 每行已经输入的字符
+    pub input: Vec<Vec<char>>,
+    /// 每行中对应汉字的输入段
+    pub hanzi: Vec<Vec<Hanzi>>,
+    /// 光标所在的行
+    pub line: usize,
+    pub evaluator: Evaluator,
+    start_at: Option<Instant>,
+    elapsed: Option<Duration>,
+    /// 手动暂停的开始时刻
+    paused_at: Option<Instant>,
+    /// 已经从用时中排除的暂停时间
+    paused: Duration,
+    last_input: Option<Instant>,
+    /// 超过这个时间没有输入时自动暂停
+    idle_pause: Option<Duration>,
+    /// 限时练习的时长
+    time_limit: Option<Duration>,
+}
+
+impl Session {
+    pub fn new(lines: Vec<Vec<char>>) -> Self {
+        let input = vec![Vec::new(); lines.len()];
+        let hanzi = vec![Vec::new(); lines.len()];
+        Self {
+            lines,
+            input,
+            hanzi,
+            line: 0,
+            evaluator: Evaluator::new(),
+            start_at: None,
+            elapsed: None,
+            paused_at: None,
+            paused: Duration::ZERO,
+            last_input: None,
+            idle_pause: None,
+            time_limit: None,
+        }
+    }
+
+    pub fn set_hanzi(&mut self, hanzi: Vec<Vec<Hanzi>>) {
+        self.evaluator.hanzi_total = hanzi.iter().flatten().map(|hanzi| hanzi.count).sum();
+        self.hanzi = hanzi;
+    }
+
+    /// 换上下一页文本继续练习，保留统计和计时
+    pub fn next_page(&mut self, lines: Vec<Vec<char>>, hanzi: Vec<Vec<Hanzi>>) {
+        self.evaluator.hanzi_total += hanzi.iter().flatten().map(|hanzi| hanzi.count).sum::<usize>();
+        self.input = vec![Vec::new(); lines.len()];
+        self.lines = lines;
+        self.hanzi = hanzi;
+        self.line = 0;
+        self.elapsed = None;
+    }
+
+    /// 清空输入和统计，重新练习同样的文本
+    pub fn reset(&mut self) {
+        self.input.iter_mut().for_each(|line| line.clear());
+        self.line = 0;
+        self.evaluator.reset();
+        self.evaluator.hanzi_total = self.hanzi.iter().flatten().map(|hanzi| hanzi.count).sum();
+        self.start_at = None;
+        self.elapsed = None;
+        self.paused_at = None;
+        self.paused = Duration::ZERO;
+        self.last_input = None;
+    }
+
+    pub fn set_idle_pause(&mut self, idle_pause: Option<Duration>) {
+        self.idle_pause = idle_pause;
+    }
+
+    pub fn set_time_limit(&mut self, time_limit: Option<Duration>) {
+        self.time_limit = time_limit;
+    }
+
+    /// 文本重新分行后换上新的行，已经输入的内容按位置搬到新的行中。
+    /// 新旧文本只有分隔词的空格和 `↵` 不同，输入正确的分隔符换成新的分隔符
+    pub fn rewrap(&mut self, lines: Vec<Vec<char>>, hanzi: Vec<Vec<Hanzi>>) {
+        let (correct, errors, completed) = self.page_counts();
+        let old: Vec<char> = self.lines.iter().flatten().cloned().collect();
+        let target: Vec<char> = lines.iter().flatten().cloned().collect();
+        let mut typed = self.input.iter().flatten().zip(old.iter().zip(target.iter()))
+            .map(|(&ch, (&old, &new))| if ch == old { new } else { ch });
+
+        self.input = lines.iter().map(|line| typed.by_ref().take(line.len()).collect()).collect();
+        self.lines = lines;
+        self.hanzi = hanzi;
+        // 光标停在最后输入的位置，输入完一整行并且回车正确时移到下一行
+        self.line = self.input.iter().rposition(|input| !input.is_empty()).unwrap_or(0);
+        let (line, idx) = self.cursor();
+        if idx == self.lines[line].len() && self.input[line].last() == Some(&'↵') && line + 1 < self.lines.len() {
+            self.line += 1;
+        }
+
+        let (new_correct, new_errors, new_completed) = self.page_counts();
+        let evaluator = &mut self.evaluator;
+        evaluator.final_chars_typed_correctly = evaluator.final_chars_typed_correctly + new_correct - correct;
+        evaluator.final_uncorrected_errors = evaluator.final_uncorrected_errors + new_errors - errors;
+        evaluator.hanzi_completed = evaluator.hanzi_completed + new_completed - completed;
+    }
+
+    /// 当前文本中 (输入正确的字符数, 没有改正的错误数, 完成的汉字数)
+    fn page_counts(&self) -> (usize, usize, usize) {
+        let mut counts = (0, 0, 0);
+        for (line, input) in self.input.iter().enumerate() {
+            for (idx, ch) in input.iter().enumerate() {
+                if *ch == self.lines[line][idx] {
+                    counts.0 += 1;
+                } else {
+                    counts.1 += 1;
+                }
+                counts.2 += self.completed_hanzi(line, idx);
+            }
+        }
+        counts
+    }
+
+    /// 光标位置 (行, 行中的字符下标)
+    pub fn cursor(&self) -> (usize, usize) {
+        (self.line, self.input.get(self.line).map_or(0, |line| line.len()))
+    }
+
+    pub fn is_started(&self) -> bool {
+        self.start_at.is_some()
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.elapsed.is_some()
+    }
+
+    /// 手动暂停，或者超过 idle_pause 没有输入
+    pub fn is_paused(&self, now: Instant) -> bool {
+        self.paused_at.is_some() || !self.idle(now).is_zero()
+    }
+
+    /// 手动暂停或继续，练习开始前和结束后不能暂停
+    pub fn toggle_pause(&mut self, now: Instant) {
+        if self.paused_at.is_some() {
+            self.resume(now);
+        } else if self.is_started() && !self.is_done() {
+            self.settle_idle(now);
+            self.paused_at = Some(now);
+        }
+    }
+
+    fn resume(&mut self, now: Instant) {
+        if let Some(paused_at) = self.paused_at.take() {
+            self.paused += now.saturating_duration_since(paused_at);
+            self.last_input = Some(now);
+        }
+    }
+
+    /// 自动暂停的时间：距上一次输入超过 idle_pause 的部分
+    fn idle(&self, now: Instant) -> Duration {
+        match (self.last_input, self.idle_pause) {
+            (Some(last_input), Some(idle_pause)) if self.paused_at.is_none() && !self.is_done() => {
+                now.saturating_duration_since(last_input).saturating_sub(idle_pause)
+            }
+            _ => Duration::ZERO,
+        }
+    }
+
+    /// 把到现在为止自动暂停的时间计入暂停时间
+    fn settle_idle(&mut self, now: Instant) {
+        self.paused += self.idle(now);
+        self.last_input = Some(now);
+    }
+
+    /// 开始以来去掉暂停后的用时，结束后为总用时
+    pub fn active(&self, now: Instant) -> Duration {
+        if let Some(elapsed) = self.elapsed {
+            return elapsed;
+        }
+        let start_at = match self.start_at {
+            Some(start_at) => start_at,
+            None => return Duration::ZERO,
+        };
+        let pausing = self.paused_at.map_or(Duration::ZERO, |paused_at| now.saturating_duration_since(paused_at));
+        now.saturating_duration_since(start_at)
+            .saturating_sub(self.paused + pausing + self.idle(now))
+    }
+
+    pub fn handle(&mut self, input: Input, now: Instant) -> Change {
+        if self.is_done() || self.lines.is_empty() || self.time_up(now) {
+            return Change::Ignored;
+        }
+        if input == Input::Backspace && self.cursor() == (0, 0) {
+            return Change::Ignored;
+        }
+        if self.start_at.is_none() {
+            self.evaluator.reset();
+            self.start_at = Some(now);
+        }
+        // 任何输入都会结束暂停
+        if self.paused_at.is_some() {
+            self.resume(now);
+        } else {
+            self.settle_idle(now);
+        }
+        match input {
+            Input::Char(ch) => {
+                let change = self.type_char(ch);
+                self.count(change, now);
+                change
+            }
+            Input::Backspace => self.backspace(),
+            Input::Enter => {
+                let change = self.type_char('↵');
+                self.count(change, now);
+                let (line, idx) = self.cursor();
+                if let Change::Typed { correct: true, .. } = change {
+                    if line + 1 < self.lines.len() {
+                        self.line += 1;
+                    }
+                }
+                if line + 1 >= self.lines.len() && idx == self.lines[line].len() {
+                    self.elapsed = Some(self.active(now));
+                }
+                change
+            }
+        }
+    }
+
+    /// 记录一次按键，被拒绝的输入不进入文本，只算作原始错误
+    fn count(&mut self, change: Change, now: Instant) {
+        let completed = match change {
+            Change::Typed { line, idx, correct: true, .. } => self.completed_hanzi(line, idx),
+            _ => 0,
+        };
+        let at = self.active(now);
+        let evaluator = &mut self.evaluator;
+        evaluator.record(at, !matches!(change, Change::Typed { correct: true, .. }));
+        evaluator.total_chars_typed += 1;
+        match change {
+            Change::Typed { correct: true, .. } => {
+                evaluator.final_chars_typed_correctly += 1;
+                evaluator.hanzi_completed += completed;
+            }
+            Change::Typed { correct: false, .. } => {
+                evaluator.total_char_errors += 1;
+                evaluator.final_uncorrected_errors += 1;
+            }
+            _ => evaluator.total_char_errors += 1,
+        }
+    }
+
+    fn type_char(&mut self, ch: char) -> Change {
+        let (line, idx) = self.cursor();
+        match self.lines[line].get(idx) {
+            Some(&expected) => {
+                self.input[line].push(ch);
+                Change::Typed { line, idx, ch, correct: expected == ch }
+            }
+            None => Change::Rejected,
+        }
+    }
+
+    /// 删除光标前的输入，在行首时删除上一行的 `↵` 并回到上一行
+    fn backspace(&mut self) -> Change {
+        if self.input[self.line].is_empty() && self.line > 0 {
+            self.line -= 1;
+        }
+        let line = self.line;
+        let completed = self.input[line].len().checked_sub(1).map_or(0, |idx| self.completed_hanzi(line, idx));
+        match self.input[line].pop() {
+            Some(ch) => {
+                let idx = self.input[line].len();
+                self.evaluator.hanzi_completed -= completed;
+                if ch == self.lines[line][idx] {
+                    self.evaluator.final_chars_typed_correctly -= 1;
+                } else {
+                    self.evaluator.final_uncorrected_errors -= 1;
+                }
+                Change::Erased { line, idx }
+            }
+            None => Change::Ignored,
+        }
+    }
+
+    /// 输入第 line 行第 idx 个字符后完成的汉字数，要求以它结尾的整段都输入正确
+    fn completed_hanzi(&self, line: usize, idx: usize) -> usize {
+        let input = &self.input[line];
+        self.hanzi.get(line).into_iter().flatten()
+            .find(|hanzi| hanzi.end == idx + 1 && input.len() >= hanzi.end)
+            .filter(|hanzi| input[hanzi.start..hanzi.end] == self.lines[line][hanzi.start..hanzi.end])
+            .map_or(0, |hanzi| hanzi.count)
+    }
+
+    /// 限时练习剩余的时间，不限时时为 None
+    pub fn remaining(&self, now: Instant) -> Option<Duration> {
+        Some(self.time_limit?.saturating_sub(self.active(now)))
+    }
+
+    /// 限时练习到时间后结束练习，返回练习是否刚刚结束
+    pub fn time_up(&mut self, now: Instant) -> bool {
+        match self.time_limit {
+            Some(time_limit) if self.is_started() && !self.is_done() && self.active(now) >= time_limit => {
+                self.elapsed = Some(time_limit);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// 练习过程中的实时统计
+    pub fn snap(&self, now: Instant) -> Option<EvalResult> {
+        self.start_at?;
+        Some(self.evaluator.snap(self.active(now)))
+    }
+
+    /// 练习结束后的统计
+    pub fn result(&self) -> Option<EvalResult> {
+        self.elapsed.map(|elapsed| self.evaluator.done(elapsed))
+    }
+
+    /// 需要输入的字符总数
+    pub fn count_char(&self) -> usize {
+        self.lines.iter().map(|line| line.len()).sum()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn session() -> Session {
+        Session::new(vec!["ab↵".chars().collect(), "c↵".chars().collect()])
+    }
+
+    #[test]
+    fn test_typing() {
+        let now = Instant::now();
+        let mut session = session();
+        assert!(!session.is_started());
+        assert_eq!(session.handle(Input::Char('a'), now), Change::Typed { line: 0, idx: 0, ch: 'a', correct: true });
+        assert_eq!(session.handle(Input::Char('x'), now), Change::Typed { line: 0, idx: 1, ch: 'x', correct: false });
+        assert_eq!(session.handle(Input::Backspace, now), Change::Erased { line: 0, idx: 1 });
+        session.handle(Input::Char('b'), now);
+        assert_eq!(session.handle(Input::Char('b'), now), Change::Typed { line: 0, idx: 2, ch: 'b', correct: false });
+        session.handle(Input::Backspace, now);
+        session.handle(Input::Enter, now);
+        assert_eq!(session.cursor(), (1, 0));
+        assert_eq!(session.handle(Input::Backspace, now), Change::Erased { line: 0, idx: 2 });
+        assert_eq!(session.cursor(), (0, 2));
+        assert_eq!(session.input[0], vec!['a', 'b']);
+        session.handle(Input::Char('x'), now);
+        assert_eq!(session.handle(Input::Char('c'), now), Change::Rejected);
+        assert_eq!(session.handle(Input::Enter, now), Change::Rejected);
+        session.reset();
+        assert_eq!(session.handle(Input::Backspace, now), Change::Ignored);
+        assert!(!session.is_started());
+    }
+
+    #[test]
+    fn test_metrics() {
+        use Input::*;
+        let now = Instant::now();
+        let mut session = session();
+        // x 打错后改正，b 打对后删除重打
+        for input in [Char('a'), Char('x'), Backspace, Char('b'), Backspace, Char('b'), Enter] {
+            session.handle(input, now);
+        }
+        // 第二行：回车和 c 都打错，最后一个 c 在行尾被拒绝
+        for input in [Enter, Char('c'), Char('c')] {
+            session.handle(input, now);
+        }
+        let evaluator = &session.evaluator;
+        assert_eq!((evaluator.total_chars_typed, evaluator.total_char_errors), (8, 4));
+        assert_eq!((evaluator.final_chars_typed_correctly, evaluator.final_uncorrected_errors), (3, 2));
+        assert_eq!(evaluator.corrected_errors(), 2);
+        assert_eq!(evaluator.raw_accuracy(), 4.0 / 8.0);
+        assert_eq!(evaluator.net_accuracy(), 3.0 / 5.0);
+        // 删除两个错误，再退回上一行重打回车
+        for input in [Backspace, Backspace, Backspace, Enter, Char('c'), Enter] {
+            session.handle(input, now);
+        }
+        assert!(session.is_done());
+        let evaluator = &session.evaluator;
+        assert_eq!((evaluator.total_chars_typed, evaluator.total_char_errors), (11, 4));
+        assert_eq!((evaluator.final_chars_typed_correctly, evaluator.final_uncorrected_errors), (5, 0));
+        assert_eq!(evaluator.corrected_errors(), 4);
+        assert_eq!(evaluator.raw_accuracy(), 7.0 / 11.0);
+        assert_eq!(evaluator.net_accuracy(), 1.0);
+    }
+
+    #[test]
+    fn test_hanzi() {
+        use Input::*;
+        let now = Instant::now();
+        // 长城 长
+        let mut session = Session::new(vec!["changcheng chang↵".chars().collect()]);
+        session.set_hanzi(vec![vec![Hanzi { start: 0, end: 10, count: 2 }, Hanzi { start: 11, end: 16, count: 1 }]]);
+        assert_eq!(session.evaluator.hanzi_total, 3);
+        for ch in "changchenx".chars() {
+            session.handle(Char(ch), now);
+        }
+        assert_eq!(session.evaluator.hanzi_completed, 0);
+        session.handle(Backspace, now);
+        session.handle(Char('g'), now);
+        assert_eq!(session.evaluator.hanzi_completed, 2);
+        session.handle(Backspace, now);
+        assert_eq!(session.evaluator.hanzi_completed, 0);
+        for ch in "g chang".chars() {
+            session.handle(Char(ch), now);
+        }
+        session.handle(Enter, now + Duration::from_secs(30));
+        assert_eq!(session.evaluator.hanzi_completed, 3);
+        match session.result() {
+            Some(EvalResult::Done(metrics)) => assert_eq!(metrics.hanzi_per_minute, Some(6.0)),
+            _ => panic!("练习没有结束"),
+        }
+        session.reset();
+        assert_eq!((session.evaluator.hanzi_completed, session.evaluator.hanzi_total), (0, 3));
+    }
+
+    #[test]
+    fn test_rewrap() {
+        use Input::*;
+        let now = Instant::now();
+        // 长城 长 分成两行后合成一行
+        let mut session = Session::new(vec!["changcheng↵".chars().collect(), "chang↵".chars().collect()]);
+        session.set_hanzi(vec![vec![Hanzi { start: 0, end: 10, count: 2 }], vec![Hanzi { start: 0, end: 5, count: 1 }]]);
+        for ch in "changcheng".chars() {
+            session.handle(Char(ch), now);
+        }
+        session.handle(Enter, now);
+        session.handle(Char('x'), now);
+        session.rewrap(
+            vec!["changcheng chang↵".chars().collect()],
+            vec![vec![Hanzi { start: 0, end: 10, count: 2 }, Hanzi { start: 11, end: 16, count: 1 }]],
+        );
+        assert_eq!(session.input, vec!["changcheng x".chars().collect::<Vec<char>>()]);
+        assert_eq!(session.cursor(), (0, 12));
+        let evaluator = &session.evaluator;
+        assert_eq!((evaluator.final_chars_typed_correctly, evaluator.final_uncorrected_errors), (11, 1));
+        assert_eq!(evaluator.hanzi_completed, 2);
+
+        // 再分成两行，输入完的第一行之后光标在第二行
+        session.handle(Backspace, now);
+        session.rewrap(
+            vec!["changcheng↵".chars().collect(), "chang↵".chars().collect()],
+            vec![vec![Hanzi { start: 0, end: 10, count: 2 }], vec![Hanzi { start: 0, end: 5, count: 1 }]],
+        );
+        assert_eq!(session.cursor(), (1, 0));
+        for ch in "chang".chars() {
+            session.handle(Char(ch), now);
+        }
+        session.handle(Enter, now);
+        assert!(session.is_done());
+        assert_eq!(session.evaluator.hanzi_completed, 3);
+    }
+
+    #[test]
+    fn test_next_page() {
+        let now = Instant::now();
+        let mut session = Session::new(vec!["长↵".chars().collect()]);
+        session.set_hanzi(vec![vec![Hanzi { start: 0, end: 1, count: 1 }]]);
+        session.handle(Input::Char('长'), now);
+        session.handle(Input::Enter, now + Duration::from_secs(1));
+        assert!(session.is_done());
+        session.next_page(vec!["城↵".chars().collect()], vec![vec![Hanzi { start: 0, end: 1, count: 1 }]]);
+        assert!(!session.is_done());
+        assert_eq!((session.cursor(), session.evaluator.hanzi_total), ((0, 0), 2));
+        session.handle(Input::Char('城'), now + Duration::from_secs(2));
+        session.handle(Input::Enter, now + Duration::from_secs(3));
+        assert_eq!(session.evaluator.final_chars_typed_correctly, 4);
+        assert_eq!(session.evaluator.hanzi_completed, 2);
+        match session.result() {
+            Some(EvalResult::Done(metrics)) => assert_eq!(metrics.elapsed, Duration::from_secs(3)),
+            _ => panic!("练习没有结束"),
+        }
+        // 重新开始只练习当前页
+        session.reset();
+        assert_eq!(session.evaluator.hanzi_total, 1);
+    }
+
+    #[test]
+    fn test_pause() {
+        let now = Instant::now();
+        let secs = |secs| now + Duration::from_secs(secs);
+        let mut session = session();
+        session.set_idle_pause(Some(Duration::from_secs(5)));
+        session.toggle_pause(now);
+        assert!(!session.is_paused(now));
+        session.handle(Input::Char('a'), now);
+        // 手动暂停 10 秒
+        session.toggle_pause(secs(1));
+        assert!(session.is_paused(secs(8)));
+        assert_eq!(session.active(secs(8)), Duration::from_secs(1));
+        session.toggle_pause(secs(11));
+        assert!(!session.is_paused(secs(11)));
+        // 超过 5 秒没有输入，之后的 15 秒自动暂停
+        session.handle(Input::Char('b'), secs(12));
+        assert!(!session.is_paused(secs(17)));
+        assert!(session.is_paused(secs(18)));
+        assert_eq!(session.active(secs(32)), Duration::from_secs(7));
+        // 暂停中的输入会结束暂停
+        session.toggle_pause(secs(40));
+        session.handle(Input::Enter, secs(50));
+        assert!(!session.is_paused(secs(50)));
+        session.handle(Input::Char('c'), secs(51));
+        session.handle(Input::Enter, secs(52));
+        match session.result() {
+            Some(EvalResult::Done(metrics)) => assert_eq!(metrics.elapsed, Duration::from_secs(9)),
+            _ => panic!("练习没有结束"),
+        }
+        session.toggle_pause(secs(53));
+        assert!(!session.is_paused(secs(100)));
+    }
+
+    #[test]
+    fn test_time_limit() {
+        let now = Instant::now();
+        let secs = |secs| now + Duration::from_secs(secs);
+        let mut session = session();
+        session.set_time_limit(Some(Duration::from_secs(10)));
+        assert_eq!(session.remaining(secs(5)), Some(Duration::from_secs(10)));
+        assert!(!session.time_up(secs(20)));
+        session.handle(Input::Char('a'), secs(20));
+        assert_eq!(session.remaining(secs(24)), Some(Duration::from_secs(6)));
+        assert!(!session.time_up(secs(29)));
+        // 到时间后的输入被忽略，用时为限定的时长
+        assert_eq!(session.handle(Input::Char('b'), secs(31)), Change::Ignored);
+        assert!(session.is_done());
+        assert!(!session.time_up(secs(32)));
+        assert_eq!(session.remaining(secs(40)), Some(Duration::ZERO));
+        match session.result() {
+            Some(EvalResult::Done(metrics)) => assert_eq!(metrics.elapsed, Duration::from_secs(10)),
+            _ => panic!("练习没有结束"),
+        }
+    }
+
+    #[test]
+    fn test_done() {
+        let now = Instant::now();
+        let mut session = session();
+        for input in [Input::Char('a'), Input::Char('b'), Input::Enter, Input::Char('c')] {
+            session.handle(input, now);
+        }
+        assert!(!session.is_done());
+        session.handle(Input::Enter, now + Duration::from_secs(6));
+        assert!(session.is_done());
+        assert_eq!(session.handle(Input::Char('a'), now), Change::Ignored);
+        assert_eq!(session.evaluator.final_chars_typed_correctly, 5);
+        match session.result() {
+            Some(EvalResult::Done(metrics)) => {
+                assert_eq!(metrics.elapsed, Duration::from_secs(6));
+                assert_eq!(metrics.speeds, vec![48.0, 0.0, 0.0, 0.0, 0.0, 12.0]);
+                assert_eq!(metrics.errors, vec![0; 6]);
+            }
+            _ => panic!("练习没有结束"),
+        }
+        session.reset();
+        assert_eq!(session.cursor(), (0, 0));
+        assert!(!session.is_done());
+    }
+}
@@ -0,0 +1,164 @@
+use std::{collections::HashMap, fs, io::Cursor, path::{Path, PathBuf}, time::Duration};
+use anyhow::{Context, Result};
+use rodio::{Decoder, OutputStream, OutputStreamHandle, Source, buffer::SamplesBuffer, source::{Buffered, SineWave}};
+
+const EXTENSIONS: [&str; 4] = ["wav", "ogg", "flac", "mp3"];
+
+/// 解码或合成好的声音，clone 时共享同一份采样
+type Clip = Buffered<SamplesBuffer<f32>>;
+
+/// 需要播放声音的事件，对应音效包中的同名文件，例如 `error.wav`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Effect {
+    Correct,
+    Error,
+    Space,
+    Enter,
+    Finish,
+}
+
+impl Effect {
+    const ALL: [Effect; 5] = [Effect::Correct, Effect::Error, Effect::Space, Effect::Enter, Effect::Finish];
+
+    fn name(&self) -> &'static str {
+        match self {
+            Effect::Correct => "correct",
+            Effect::Error => "error",
+            Effect::Space => "space",
+            Effect::Enter => "enter",
+            Effect::Finish => "finish",
+        }
+    }
+}
+
+/// 按键音，没有可用的音频设备时为 None
+pub struct Sound {
+    // 需要一直持有，否则无法播放
+    _stream: OutputStream,
+    handle: OutputStreamHandle,
+    clips: HashMap<Effect, Clip>,
+    volume: f32,
+}
+
+impl Sound {
+    /// 从音效包目录加载声音，不指定目录时使用以 pitch 为基准音高合成的声音
+    pub fn new(pack: Option<&Path>, volume: f32, pitch: f32) -> Result<Option<Self>> {
+        let (stream, handle) = match OutputStream::try_default() {
+            Ok(res) => res,
+            Err(_) => return Ok(None),
+        };
+        let clips = match pack {
+            Some(dir) => load_pack(dir)?,
+            None => synth(pitch),
+        };
+        Ok(Some(Self { _stream: stream, handle, clips, volume: volume.clamp(0.0, 1.0) }))
+    }
+
+    pub fn play(&self, effect: Effect) {
+        let clip = match self.clips.get(&effect) {
+            Some(clip) => clip,
+            None => return,
+        };
+        // 直接混入输出流，播放失败不影响打字
+        let _ = self.handle.play_raw(clip.clone().amplify(self.volume));
+    }
+}
+
+fn decode(data: Vec<u8>) -> Result<Clip> {
+    let decoder = Decoder::new(Cursor::new(data))?;
+    let (channels, sample_rate) = (decoder.channels(), decoder.sample_rate());
+    let samples = decoder.convert_samples().collect::<Vec<f32>>();
+    Ok(SamplesBuffer::new(channels, sample_rate, samples).buffered())
+}
+
+/// 频率为 freq 的正弦波，结尾淡出避免爆音
+fn tone(freq: f32, millis: u64, amplitude: f32) -> Vec<f32> {
+    let mut source = SineWave::new(freq).take_duration(Duration::from_millis(millis));
+    source.set_filter_fadeout();
+    source.amplify(amplitude).collect()
+}
+
+fn clip(samples: Vec<f32>) -> Clip {
+    SamplesBuffer::new(1, SineWave::new(0.0).sample_rate(), samples).buffered()
+}
+
+/// 内置的合成声音：按键为短促的咔哒声，错误为低沉的蜂鸣，完成时播放上行的三个音
+fn synth(pitch: f32) -> HashMap<Effect, Clip> {
+    let finish = [1.0, 1.25, 1.5]
+        .iter()
+        .flat_map(|ratio| tone(pitch * ratio, 100, 0.3))
+        .collect();
+    HashMap::from([
+        (Effect::Correct, clip(tone(pitch, 15, 0.3))),
+        (Effect::Space, clip(tone(pitch * 0.75, 20, 0.3))),
+        (Effect::Enter, clip(tone(pitch * 1.5, 40, 0.3))),
+        (Effect::Error, clip(tone(pitch / 2.0, 120, 0.4))),
+        (Effect::Finish, clip(finish)),
+    ])
+}
+
+/// 音效包中某个事件对应的文件，按 EXTENSIONS 的顺序查找
+fn pack_file(dir: &Path, effect: Effect) -> Option<PathBuf> {
+    EXTENSIONS
+        .iter()
+        .map(|ext| dir.join(effect.name()).with_extension(ext))
+        .find(|path| path.is_file())
+}
+
+/// 缺少的按键音使用 correct，缺少 finish 时结束不播放声音
+fn load_pack(dir: &Path) -> Result<HashMap<Effect, Clip>> {
+    if !dir.is_dir() {
+        return Err(anyhow::Error::msg(format!("音效包目录不存在: {}", dir.display())));
+    }
+    let mut clips = HashMap::new();
+    for effect in Effect::ALL {
+        if let Some(path) = pack_file(dir, effect) {
+            let clip = decode(fs::read(&path)?).with_context(|| format!("无法解码音效文件: {}", path.display()))?;
+            clips.insert(effect, clip);
+        }
+    }
+    if let Some(correct) = clips.get(&Effect::Correct).cloned() {
+        for effect in [Effect::Error, Effect::Space, Effect::Enter] {
+            clips.entry(effect).or_insert_with(|| correct.clone());
+        }
+    }
+    Ok(clips)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_pack_file() {
+        let dir = std::env::temp_dir().join("typlap-sound-pack");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("error.ogg"), b"").unwrap();
+        fs::write(dir.join("enter.mp3"), b"").unwrap();
+        fs::write(dir.join("enter.wav"), b"").unwrap();
+        assert_eq!(pack_file(&dir, Effect::Error), Some(dir.join("error.ogg")));
+        assert_eq!(pack_file(&dir, Effect::Enter), Some(dir.join("enter.wav")));
+        assert_eq!(pack_file(&dir, Effect::Finish), None);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_synth() {
+        let clips = synth(1000.0);
+        assert!(Effect::ALL.iter().all(|effect| clips.contains_key(effect)));
+        // 48000Hz 单声道
+        assert_eq!(clips[&Effect::Correct].clone().count(), 720);
+        assert_eq!(clips[&Effect::Finish].clone().count(), 3 * 4800);
+        assert!(clips[&Effect::Error].clone().all(|sample| sample.abs() <= 0.4));
+    }
+
+    #[test]
+    fn test_load_pack() {
+        let dir = std::env::temp_dir().join("typlap-sound-load");
+        fs::create_dir_all(&dir).unwrap();
+        assert!(load_pack(&dir).unwrap().is_empty());
+
+        fs::remove_dir_all(&dir).unwrap();
+        assert!(load_pack(&dir).is_err());
+    }
+}
@@ -1,5 +1,3 @@
-use std::{io::{Read}, path::{Path}, fs::File};
-use anyhow::Result;
 use rand::prelude::SliceRandom;
 
 
@@ -22,34 +20,35 @@ impl TextGenerator {
         }
     }
 
-    pub fn read_content(&mut self, file_path: &str) -> Result<()>{
-        let path =  Path::new(file_path);
-        let mut file = File::open(path)?;
-        let mut content = String::new();
-        file.read_to_string(&mut content)?;
-       
+    /// 从词库内容中按空白切分出词
+    pub fn load_content(&mut self, content: &str) {
         self.words = content
             .split_ascii_whitespace()
-            .map(|line| String::from(line))
+            .map(String::from)
             .collect();
 
         self.length = self.words.len();
-        Ok(())
     }
 
     pub fn into_iter(&self) -> IntoIter{
         let mut rng = rand::thread_rng();
-        let mut choice_idx = (1..self.length).collect::<Vec<usize>>();
+        let mut choice_idx = (0..self.length).collect::<Vec<usize>>();
         choice_idx.shuffle(&mut rng);
         IntoIter { 
             words: self.words.clone(), 
-            choice_idx: choice_idx, 
+            choice_idx, 
             cur_idx: 0, 
         }
     }
 
 }
 
+impl Default for TextGenerator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Iterator for IntoIter {
     type Item = String;
 
@@ -69,11 +68,19 @@ mod test {
     #[test]
     pub fn text_gen(){
         let mut tg = TextGenerator::new();
-        tg.read_content("./src/text/it.txt").unwrap();
+        tg.load_content(include_str!("../text/it.txt"));
         let mut iter = tg.into_iter();
-        for i in iter.next() {
-            println!("{:?}", i);
+        if let Some(word) = iter.next() {
+            println!("{:?}", word);
         }
-        "↵";
+    }
+
+    #[test]
+    fn test_all_words() {
+        let mut tg = TextGenerator::new();
+        tg.load_content("一 二\n三");
+        let mut words: Vec<String> = tg.into_iter().collect();
+        words.sort();
+        assert_eq!(words, vec!["一", "三", "二"]);
     }
 }
\ No newline at end of file
@@ -0,0 +1,106 @@
+use crossterm::style::{Attribute, Color, ContentStyle, Stylize};
+use serde::Deserialize;
+
+/// 可以在配置文件中选择的主题
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ThemeName {
+    Default,
+    /// 红绿色盲友好，用蓝色和黄色区分正确与错误
+    Colorblind,
+    /// 粗体亮色，正确为绿色，错误为红底白字
+    HighContrast,
+    /// 不使用颜色，错误用反色和下划线标出
+    Monochrome,
+}
+
+/// 界面中各部分的样式
+#[derive(Clone, Copy, Debug)]
+pub struct Theme {
+    /// 输入正确的字符
+    pub correct: ContentStyle,
+    /// 输入错误的字符
+    pub error: ContentStyle,
+    /// 准确率、速度等统计数字
+    pub stat: ContentStyle,
+    /// 真实准确率
+    pub stat_alt: ContentStyle,
+    /// 结束时的速度
+    pub speed: ContentStyle,
+    /// 页脚中的按键
+    pub key: ContentStyle,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        ThemeName::Default.into()
+    }
+}
+
+fn color(color: Color) -> ContentStyle {
+    ContentStyle::new().with(color)
+}
+
+impl From<ThemeName> for Theme {
+    fn from(name: ThemeName) -> Self {
+        let bold = ContentStyle::new().attribute(Attribute::Bold);
+        match name {
+            ThemeName::Default => Theme {
+                correct: bold.with(Color::Green),
+                error: bold.with(Color::Red),
+                stat: color(Color::Magenta),
+                stat_alt: color(Color::Cyan),
+                speed: color(Color::Green),
+                key: bold.with(Color::Blue),
+            },
+            ThemeName::Colorblind => Theme {
+                correct: bold.with(Color::Blue),
+                error: bold.with(Color::Yellow).attribute(Attribute::Underlined),
+                stat: color(Color::Blue),
+                stat_alt: color(Color::Yellow),
+                speed: color(Color::Blue),
+                key: bold.with(Color::Cyan),
+            },
+            ThemeName::HighContrast => Theme {
+                correct: bold.with(Color::Green),
+                error: bold.with(Color::White).on(Color::Red),
+                stat: bold.with(Color::Yellow),
+                stat_alt: bold.with(Color::Cyan),
+                speed: bold.with(Color::Yellow),
+                key: bold.with(Color::Black).on(Color::White),
+            },
+            ThemeName::Monochrome => Theme {
+                correct: bold,
+                error: bold.attribute(Attribute::Reverse).attribute(Attribute::Underlined),
+                stat: bold,
+                stat_alt: bold,
+                speed: bold,
+                key: bold.attribute(Attribute::Underlined),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_monochrome() {
+        let theme = Theme::from(ThemeName::Monochrome);
+        for style in [theme.correct, theme.error, theme.stat, theme.stat_alt, theme.speed, theme.key] {
+            assert_eq!(style.foreground_color, None);
+            assert_eq!(style.background_color, None);
+        }
+        assert!(theme.error.attributes.has(Attribute::Reverse));
+        assert!(!theme.correct.attributes.has(Attribute::Reverse));
+    }
+
+    #[test]
+    fn test_high_contrast() {
+        // 输入正确的字符与未输入的白色文本区分开
+        let theme = Theme::from(ThemeName::HighContrast);
+        assert_eq!(theme.correct.foreground_color, Some(Color::Green));
+        assert_eq!(theme.error.background_color, Some(Color::Red));
+    }
+}
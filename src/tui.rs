@@ -1,15 +1,31 @@
-use std::io::{Stdout, stdout, Write};
 use anyhow::Result;
-use crossterm::{terminal::{self, size, Clear, SetSize}, execute, cursor::{self, MoveTo}, style::{Print, Stylize, Color, Attribute}};
-use crate::{textgen, utils::util::{self, is_chinese, transform_punctuation,}, evaluator::EvalResult};
-
-const MIN_HEIGHT:u16 = 6;
-const MIN_WIDTH:u16 = 45;
+use crossterm::{cursor::CursorShape, style::{ContentStyle, StyledContent}};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+use typlap::{textgen, utils::util::Scheme, evaluator::EvalResult, page::{self, Lines, Word}, session::Session};
+use crate::{backend::Backend, chart, keys::KeyBindings, theme::Theme};
+
+const MIN_HEIGHT:u16 = 7;
+const MIN_WIDTH:u16 = 50;
+/// 结果页速度图的最大宽度和高度（字符）
+const CHART_WIDTH: usize = 40;
+const CHART_HEIGHT: usize = 4;
+/// 顶部边框中计时所占的宽度
+const TIMER_WIDTH: usize = 14;
 
 pub struct TextArea {
     pub word_iter: textgen::IntoIter,
-    pub raw_text: Vec<Vec<char>>,
-    pub pinyin_text: Vec<String>,
+    /// 当前文本中的词，窗口大小改变后据此重新分行
+    pub words: Vec<Word>,
+    /// 上一页放不下的词，放到下一页的开头
+    pub pending: Option<Word>,
+    /// 这一轮练习已经生成的词数，包括之前的页
+    pub generated: usize,
+    /// 这一轮练习之前的页的字符数
+    pub previous_chars: usize,
+    /// 按当前窗口宽度排好的各行
+    pub lines: Lines,
+    /// 当前输入方案下无法转换而跳过的词
+    pub skipped: Vec<String>,
     pub pos: Vec<LinePos>,
     pub cols: u16,
     pub rows: u16,
@@ -18,40 +34,55 @@ pub struct TextArea {
 pub struct LinePos {col: u16, row: u16}
 
 
-pub struct Tui {
-    pub stdout: Stdout,
+pub struct Tui<B: Backend> {
+    pub backend: B,
     pub text: TextArea,
     pub cols: u16,
     pub rows: u16,
     pub cursor_col: u16,
     pub cursor_row: u16,
-    pub input: String,
     pub chinese: bool,
+    pub scheme: Scheme,
+    pub ascii_fallback: bool,
+    /// 每轮练习的词数，一页放不下时分页，None 时填满文本区域
+    pub length: Option<usize>,
+    /// 限时练习，文本一页一页生成到时间结束，不受 length 限制
+    pub timed: bool,
+    pub cursor_shape: CursorShape,
+    /// 页脚中显示的 (按键, 说明)
+    pub footer: Vec<(String, &'static str)>,
+    pub theme: Theme,
 }
 
-impl Tui {
-    pub fn new(word_iter: textgen::IntoIter) -> Result<Self> {
-        let (cols, rows) = size()?;
-        let mut stdout = stdout();
-        if cols < MIN_WIDTH || rows < MIN_HEIGHT {
-            execute!(stdout, SetSize(MIN_WIDTH, MIN_HEIGHT)).map_err(|_| anyhow::Error::msg("终端窗口太小!"))?;
-        }
+impl<B: Backend> Tui<B> {
+    pub fn new(word_iter: textgen::IntoIter, backend: B) -> Result<Self> {
+        let (cols, rows) = backend.size()?;
         Ok(Self { 
-            stdout: stdout,
+            backend,
             text: TextArea {
-                word_iter: word_iter,
-                raw_text: Vec::new(),
-                pinyin_text: Vec::new(), 
+                word_iter,
+                words: Vec::new(),
+                pending: None,
+                generated: 0,
+                previous_chars: 0,
+                lines: Lines::default(),
+                skipped: Vec::new(),
                 pos: Vec::new(), 
                 cols: 0, 
                 rows: 0,
             },
-            cols: cols,
-            rows: rows,
+            cols,
+            rows,
             cursor_col: 0,
             cursor_row: 0,
-            input: String::new(),
             chinese: false,
+            scheme: Scheme::Pinyin,
+            ascii_fallback: false,
+            length: None,
+            timed: false,
+            cursor_shape: CursorShape::Line,
+            footer: KeyBindings::default().footer(),
+            theme: Theme::default(),
         })
     }
 
@@ -60,16 +91,109 @@ impl Tui {
         self.rows = rows;
     }
 
+    pub fn set_scheme(&mut self, scheme: Scheme) {
+        self.scheme = scheme;
+    }
+
+    /// 非 ASCII 字符改为输入对应的 ASCII 字符，例如 é 输入 e
+    pub fn set_ascii_fallback(&mut self, ascii_fallback: bool) {
+        self.ascii_fallback = ascii_fallback;
+    }
+
+    pub fn set_length(&mut self, length: Option<usize>) {
+        self.length = length;
+    }
+
+    pub fn set_timed(&mut self, timed: bool) {
+        self.timed = timed;
+    }
+
+    pub fn set_cursor_shape(&mut self, cursor_shape: CursorShape) {
+        self.cursor_shape = cursor_shape;
+    }
+
+    pub fn set_footer(&mut self, footer: Vec<(String, &'static str)>) {
+        self.footer = footer;
+    }
+
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+    }
+
+    /// 输入正确的字符使用 correct 样式，错误的使用 error 样式
+    fn styled(&self, expected: char, ch: char) -> StyledContent<char> {
+        // 宽度不同的字符会覆盖相邻的字符，显示原字符
+        let shown = if ch.width() == expected.width() { ch } else { expected };
+        let style = if expected == ch { self.theme.correct } else { self.theme.error };
+        style.apply(shown)
+    }
+
     pub fn init(&mut self) -> Result<()>{
-        terminal::enable_raw_mode()?;
-        execute!(self.stdout, Clear(terminal::ClearType::All), terminal::SetSize(self.cols, self.rows), cursor::Show, cursor::SetCursorShape(cursor::CursorShape::Line))?;
+        self.backend.enable_raw_mode()?;
+        self.backend.clear()?;
+        self.backend.show_cursor(self.cursor_shape)?;
+        // 窗口太小时先不生成文本，等待窗口变大
+        if self.window_too_small() {
+            self.text.lines.raw_text.clear();
+            return self.display_too_small();
+        }
+        self.text.generated = 0;
+        self.text.previous_chars = 0;
         self.init_bound()?;
         self.init_text()?;
         self.init_footer()?;
         self.cursor_col  = self.text.pos[0].col;
         self.cursor_row = self.text.pos[0].row;
-        execute!(self.stdout, MoveTo(self.cursor_col, self.cursor_row))?;
-        self.stdout.flush()?;
+        self.backend.move_to(self.cursor_col, self.cursor_row)?;
+        self.backend.flush()?;
+        Ok(())
+    }
+
+    fn window_too_small(&self) -> bool {
+        self.cols < MIN_WIDTH || self.rows < MIN_HEIGHT
+    }
+
+    /// 终端窗口太小，或者已经生成的文本在当前大小下放不下
+    pub fn too_small(&self) -> bool {
+        self.too_small_reason().is_some()
+    }
+
+    /// 窗口放不下时说明哪个方向不够
+    fn too_small_reason(&self) -> Option<String> {
+        if self.cols < MIN_WIDTH {
+            return Some(format!("width {}, need at least {} columns", self.cols, MIN_WIDTH));
+        }
+        if self.rows < MIN_HEIGHT {
+            return Some(format!("height {}, need at least {} rows", self.rows, MIN_HEIGHT));
+        }
+        let width = self.text.lines.target_text.iter().map(|line| line.width()).max().unwrap_or(0) + 2;
+        if width > self.cols as usize {
+            return Some(format!("width {}, the text needs {} columns", self.cols, width));
+        }
+        let height = self.text.pos.last().map_or(0, |pos| pos.row) + 2;
+        if height > self.rows {
+            return Some(format!("height {}, the text needs {} rows", self.rows, height));
+        }
+        None
+    }
+
+    /// 提示窗口太小，等待调整窗口大小
+    fn display_too_small(&mut self) -> Result<()> {
+        let lines = [
+            String::from("Terminal too small"),
+            self.too_small_reason().unwrap_or_default(),
+            String::from("please enlarge the window"),
+        ];
+        self.backend.clear()?;
+        self.backend.hide_cursor()?;
+        let top = self.rows.saturating_sub(lines.len() as u16) / 2;
+        for (idx, line) in lines.iter().enumerate() {
+            let line: String = line.chars().take(self.cols as usize).collect();
+            let col = self.cols.saturating_sub(line.len() as u16) / 2;
+            self.backend.move_to(col, top + idx as u16)?;
+            self.backend.print(line)?;
+        }
+        self.backend.flush()?;
         Ok(())
     }
 
@@ -77,10 +201,12 @@ impl Tui {
         for y in 0..self.rows {
             for x in 0..self.cols {
               if y == 0 || y == self.rows - 1 {
-                execute!(self.stdout, cursor::MoveTo(x,y), Print("-"))?;
+                self.backend.move_to(x, y)?;
+                self.backend.print("-")?;
               }
               if x == 0 || x == self.cols - 1 {
-                execute!(self.stdout, cursor::MoveTo(x,y), Print("|"))?;
+                self.backend.move_to(x, y)?;
+                self.backend.print("|")?;
               }
             }
         }
@@ -90,302 +216,440 @@ impl Tui {
     pub fn display_result(&mut self, res: EvalResult) -> Result<()> {
         match res {
             EvalResult::Snap(acc, wpm) => {
+                if self.too_small() {
+                    return Ok(());
+                }
                 let clear_pad = " ".repeat((self.cols - 2) as usize);
                 let acc_prefix = "current Accuracy: ";
                 let wpm_prefix = ", current Wpm: ";
-                let acc = format!("{:.1}%", acc * 100.0).with(Color::Magenta);
-                let wpm = format!("{:.1}", wpm).with(Color::Magenta);
+                let acc = self.theme.stat.apply(format!("{:.1}%", acc * 100.0));
+                let wpm = self.theme.stat.apply(format!("{:.1}", wpm));
                 let length = acc_prefix.len() + wpm_prefix.len() + acc.content().len() + wpm.content().len();
-                let cols = (self.cols - length as u16) / 2;
-                execute!(
-                    self.stdout,
-                    cursor::MoveTo(1,1),
-                    Print(clear_pad),
-                    cursor::MoveTo(cols,1),
-                    Print(acc_prefix),
-                    Print(acc),
-                    Print(wpm_prefix),
-                    Print(wpm),
-                    cursor::MoveTo(self.cursor_col, self.cursor_row),
-                )?;
+                let cols = self.cols.saturating_sub(length as u16) / 2;
+                self.backend.move_to(1, 1)?;
+                self.backend.print(clear_pad)?;
+                self.backend.move_to(cols, 1)?;
+                self.backend.print(acc_prefix)?;
+                self.backend.print_styled(acc)?;
+                self.backend.print(wpm_prefix)?;
+                self.backend.print_styled(wpm)?;
+                self.backend.move_to(self.cursor_col, self.cursor_row)?;
 
             },
-            EvalResult::Done(delta, acc, real_acc, wpm) => {
-                execute!(self.stdout, Clear(terminal::ClearType::All), terminal::SetSize(self.cols, self.rows), cursor::Hide)?;
+            EvalResult::Done(metrics) => {
+                if self.window_too_small() {
+                    return self.display_too_small();
+                }
+                self.backend.clear()?;
+                self.backend.hide_cursor()?;
                 self.init_bound()?;
-                let first = format!("Tooks {}s for {} words", delta.as_secs(), self.count_char());
-                let acc = format!("Accuracy: {:.1}%", acc * 100.0).with(Color::Magenta);
-                let real_acc = format!("Real Accuracy: {:.1}%", real_acc * 100.0).with(Color::Cyan);
-                let wpm_prefix = format!("Speed: ");
-                let wpm = format!("{:.1} wpm", wpm).with(Color::Green);
-                let wpm_suffix = format!(" (words per minute)");
-                
-                let rows = self.rows/2 - 2;
-                let wpm_length = (wpm_prefix.len() + wpm.content().len() + wpm_suffix.len()) as u16;
-                execute!(
-                    self.stdout,
-                    MoveTo((self.cols - first.len()  as u16)/2, rows),
-                    Print(first),
-                    MoveTo((self.cols - acc.content().len()  as u16)/2, rows+1),
-                    Print(acc),
-                    MoveTo((self.cols - real_acc.content().len()  as u16)/2, rows+2),
-                    Print(real_acc),
-                    MoveTo((self.cols - wpm_length)/2, rows+3),
-                    Print(wpm_prefix),
-                    Print(wpm),
-                    Print(wpm_suffix),
-                )?;
+                let plain = ContentStyle::new();
+                let mut lines = vec![
+                    vec![plain.apply(format!("Tooks {}s for {} words", metrics.elapsed.as_secs(), self.count_char()))],
+                    vec![self.theme.stat.apply(format!("Accuracy: {:.1}%", metrics.accuracy * 100.0))],
+                    vec![self.theme.stat_alt.apply(format!("Raw Accuracy: {:.1}%", metrics.raw_accuracy * 100.0))],
+                    vec![
+                        plain.apply(String::from("Speed: ")),
+                        self.theme.speed.apply(format!("{:.1} wpm", metrics.wpm)),
+                        plain.apply(format!(" (raw {:.1} wpm, {:.0} cpm)", metrics.raw_wpm, metrics.cpm)),
+                    ],
+                    vec![
+                        plain.apply(String::from("Consistency: ")),
+                        self.theme.stat.apply(format!("{:.1}%", metrics.consistency * 100.0)),
+                    ],
+                ];
+                // 中文文本另起一行显示字/分钟，窗口放不下时省略
+                if let Some(hanzi_per_minute) = metrics.hanzi_per_minute {
+                    if lines.len() + 2 < self.rows as usize {
+                        lines.push(vec![
+                            plain.apply(String::from("Chinese: ")),
+                            self.theme.speed.apply(format!("{:.1} 字/分钟", hanzi_per_minute)),
+                        ]);
+                    }
+                }
+                if !self.text.skipped.is_empty() && lines.len() + 2 < self.rows as usize {
+                    lines.push(vec![self.theme.stat_alt.apply(format!("Skipped {} words without a reading", self.text.skipped.len()))]);
+                }
+                // 窗口够高时画出每秒的速度，标出有错误的秒
+                if lines.len() + CHART_HEIGHT + 5 <= self.rows as usize {
+                    let width = (self.cols as usize).saturating_sub(4).min(CHART_WIDTH);
+                    let max = metrics.speeds.iter().cloned().fold(0.0, f64::max);
+                    lines.push(vec![]);
+                    lines.push(vec![plain.apply(format!("Speed per second (max {:.0} wpm, x = errors)", max))]);
+                    for line in chart::braille(&metrics.speeds, width, CHART_HEIGHT) {
+                        lines.push(vec![self.theme.speed.apply(line)]);
+                    }
+                    lines.push(chart::marks(&metrics.errors, width, 'x').chars().map(|ch| {
+                        let style = if ch == ' ' { plain } else { self.theme.error };
+                        style.apply(ch.to_string())
+                    }).collect());
+                }
+                let top = self.rows.saturating_sub(lines.len() as u16) / 2;
+                for (idx, line) in lines.into_iter().enumerate() {
+                    let width = line.iter().map(|part| part.content().width()).sum::<usize>() as u16;
+                    self.backend.move_to(self.cols.saturating_sub(width) / 2, top + idx as u16)?;
+                    for part in line {
+                        self.backend.print_styled(part)?;
+                    }
+                }
                 self.init_footer()?;
             },
         }
         Ok(())
     }
 
+    /// 暂停时在顶部显示提示，代替实时统计
+    pub fn display_paused(&mut self) -> Result<()> {
+        if self.too_small() {
+            return Ok(());
+        }
+        let clear_pad = " ".repeat((self.cols - 2) as usize);
+        let paused = self.theme.stat_alt.apply("paused, type to resume");
+        let cols = self.cols.saturating_sub(paused.content().len() as u16) / 2;
+        self.backend.move_to(1, 1)?;
+        self.backend.print(clear_pad)?;
+        self.backend.move_to(cols, 1)?;
+        self.backend.print_styled(paused)?;
+        self.backend.move_to(self.cursor_col, self.cursor_row)?;
+        Ok(())
+    }
+
+    /// 在顶部边框的左侧显示计时
+    pub fn display_timer(&mut self, timer: &str) -> Result<()> {
+        if self.too_small() {
+            return Ok(());
+        }
+        let label = format!(" {} ", timer);
+        let pad = "-".repeat(TIMER_WIDTH.saturating_sub(label.len()));
+        self.backend.move_to(2, 0)?;
+        self.backend.print_styled(self.theme.stat.apply(label))?;
+        self.backend.print(pad)?;
+        self.backend.move_to(self.cursor_col, self.cursor_row)?;
+        Ok(())
+    }
+
     pub fn init_text(&mut self) -> Result<()>{
-        self.text.raw_text.clear();
-        self.text.pinyin_text.clear();
-        self.text.pos.clear();
-        self.input.clear();
-        let max_text_rows = self.rows / 4;
-        let max_text_cols = self.cols / 5 * 3;
+        self.text.words = self.take_words();
+        self.build_lines();
+        self.place_lines();
+        self.draw_text()?;
+        Ok(())
+    }
 
-        let cursor_rows = (self.rows - max_text_rows) / 2 - self.rows / 6;
-        let mut is_empty = false;
-        while self.text.raw_text.len() < max_text_rows as usize && !is_empty{
-            let mut line = String::new();
-            let mut raw_line = String::new();
-            let mut chinese_cnt = Vec::<usize>::new();
-            loop {
-                let word = self.text.word_iter.next();
-                match word {
-                    Some(w) => {
-                        let (cnt, pinyin) = util::transform(&w);
-                        if line.len() + pinyin.len() + 1 < max_text_cols as usize {
-                            line.push_str(&pinyin);
-                            line.push(' ');
-                            raw_line.push_str(&w);
-                            raw_line.push(' ');
-                            chinese_cnt.push(cnt);
-                        } else {
-                            raw_line.pop();
-                            line.pop();
-                            break
-                        }
-                    }
-                    None => {
-                        is_empty = true;
-                        break
-                    }
-                }
+    /// 生成一页的词，放不下的词留到下一页
+    fn take_words(&mut self) -> Vec<Word> {
+        let max_text_rows = (self.rows / 4) as usize;
+        let max_text_cols = (self.cols / 5 * 3) as usize;
+        let limit = if self.timed { None } else { self.length };
+
+        let mut words = Vec::new();
+        while limit != Some(self.text.generated) {
+            let word = match self.text.pending.take() {
+                Some(word) => word,
+                None => match self.next_word() {
+                    Some(word) => word,
+                    None => break,
+                },
+            };
+            words.push(word);
+            if page::wrap(&words, max_text_cols).len() > max_text_rows {
+                self.text.pending = words.pop();
+                break
             }
-            line.push('↵');
-            raw_line.push('↵');
-
-            if line.ne(&raw_line) {
-                let raw_line = line.split_ascii_whitespace().zip(raw_line.split_ascii_whitespace()).zip(chinese_cnt).map(|((pinyin, hans), cnt)| {
-                    let length = pinyin.len();
-                    let sent_len = hans.chars().count() + cnt;
-                    let mut new = String::new();
-                    if sent_len < length {
-                        let pad_cnt_left = (length - sent_len) / 2;
-                        let pad_cnt_right = length - pad_cnt_left - sent_len;
-                        new.push_str(&" ".repeat(pad_cnt_left));
-                        new.push_str(&hans);
-                        new.push_str(&" ".repeat(pad_cnt_right));
-                    } else {
-                        new.push_str(&hans);
-                    }
-                    new
-                }).collect::<Vec<String>>().join(" ");
-
-                execute!(
-                    self.stdout,
-                    MoveTo((self.cols - line.len() as u16) / 2, cursor_rows + (self.text.raw_text.len() * 2) as u16),
-                    Print(&raw_line),
-                    MoveTo((self.cols - line.len() as u16) / 2, cursor_rows + (self.text.raw_text.len() * 2 + 1) as u16),
-                    Print(&line)
-                )?;
-                self.text.pos.push(LinePos { 
-                    col: (self.cols - line.len() as u16) / 2,
-                    row: cursor_rows + (self.text.raw_text.len() * 2 + 1) as u16,
-                });
-                self.text.raw_text.push(line.chars().collect::<Vec<char>>());
-                self.text.pinyin_text.push(raw_line);
+            self.text.generated += 1;
+        }
+        words
+    }
+
+    /// 从词库中取出下一个能在当前输入方案下转换的词
+    fn next_word(&mut self) -> Option<Word> {
+        loop {
+            let w = self.text.word_iter.next()?;
+            match Word::new(&w, self.scheme, self.ascii_fallback) {
+                Some(word) => return Some(word),
+                // 当前输入方案下无法转换的词跳过，退出时列出
+                None => self.text.skipped.push(w),
+            }
+        }
+    }
+
+    /// 当前页输入完后换上下一页，这一轮的词已经生成完时返回 false
+    pub fn next_page(&mut self) -> Result<bool> {
+        if !self.timed && !matches!(self.length, Some(length) if self.text.generated < length) {
+            return Ok(false);
+        }
+        let words = self.take_words();
+        if words.is_empty() {
+            return Ok(false);
+        }
+        self.text.previous_chars += self.text.lines.raw_text.iter().map(|line| line.len()).sum::<usize>();
+        self.text.words = words;
+        self.build_lines();
+        self.place_lines();
+        self.reset()?;
+        self.backend.flush()?;
+        Ok(true)
+    }
+
+    /// 按当前窗口宽度把词排成行
+    fn build_lines(&mut self) {
+        let max_text_cols = (self.cols / 5 * 3) as usize;
+        self.text.lines = Lines::build(&self.text.words, max_text_cols);
+    }
+
+    /// 按当前终端大小计算每一行的位置，有提示行时每行占两行
+    fn place_lines(&mut self) {
+        let max_text_rows = self.rows / 4;
+        let cursor_rows = (self.rows - max_text_rows) / 2 - self.rows / 6;
+        let two_rows = self.text.lines.pinyin_text.iter().any(|hint| !hint.is_empty());
+        self.text.pos = self.text.lines.target_text.iter().enumerate().map(|(idx, line)| {
+            let col = self.cols.saturating_sub(line.width() as u16) / 2;
+            let row = if two_rows {
+                cursor_rows + (idx * 2) as u16 + 1
             } else {
-                self.text.pos.push(LinePos { 
-                    col: (self.cols - line.len() as u16) / 2,
-                    row: cursor_rows + self.text.raw_text.len() as u16,
-                });
-                execute!(self.stdout, MoveTo((self.cols - line.len() as u16) / 2, cursor_rows + self.text.raw_text.len() as u16), Print(&line))?;
-                self.text.raw_text.push(line.chars().collect::<Vec<char>>());
-                self.text.pinyin_text.push(String::new());
+                cursor_rows + idx as u16
+            };
+            LinePos { col, row }
+        }).collect();
+    }
+
+    fn draw_text(&mut self) -> Result<()> {
+        for (idx, (line, pinyin)) in self.text.lines.target_text.iter().zip(self.text.lines.pinyin_text.iter()).enumerate() {
+            let pos = &self.text.pos[idx];
+            if !pinyin.is_empty() {
+                self.backend.move_to(pos.col, pos.row - 1)?;
+                self.backend.print(pinyin)?;
             }
+            self.backend.move_to(pos.col, pos.row)?;
+            self.backend.print(line)?;
         }
         Ok(())
     }
 
+    /// 重新显示已经输入的字符
+    fn draw_input(&mut self, session: &Session) -> Result<()> {
+        for (line, input) in session.input.iter().enumerate() {
+            for (idx, ch) in input.iter().enumerate() {
+                self.display_typed(line, idx, *ch)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// 启动时窗口太小，还没有生成文本
+    pub fn has_text(&self) -> bool {
+        !self.text.lines.raw_text.is_empty()
+    }
+
+    /// 终端大小改变后按新的宽度重新分行，保留已经输入的内容
+    pub fn resize(&mut self, session: &mut Session) -> Result<()> {
+        if !self.window_too_small() {
+            self.build_lines();
+            session.rewrap(self.text.lines.raw_text.clone(), self.text.lines.hanzi.clone());
+        }
+        self.place_lines();
+        let (line, idx) = session.cursor();
+        self.cursor_row = self.text.pos[line].row;
+        self.cursor_col = self.column_at(line, idx);
+        if self.too_small() {
+            return self.display_too_small();
+        }
+        self.backend.clear()?;
+        self.backend.show_cursor(self.cursor_shape)?;
+        self.init_bound()?;
+        self.draw_text()?;
+        self.draw_input(session)?;
+        self.init_footer()?;
+        self.backend.move_to(self.cursor_col, self.cursor_row)?;
+        self.backend.flush()?;
+        Ok(())
+    }
+
+    /// 重新练习当前页，之前的页不再计入这一轮
+    pub fn restart(&mut self) -> Result<()> {
+        self.text.generated = self.text.words.len();
+        self.text.previous_chars = 0;
+        self.reset()
+    }
+
     pub fn reset(&mut self) -> Result<()> {
-        self.input.clear();
         self.cursor_col = self.text.pos[0].col;
         self.cursor_row = self.text.pos[0].row;
-        execute!(self.stdout,  Clear(terminal::ClearType::All))?; 
+        self.backend.clear()?;
         self.init_bound()?;
-        for (idx, (line, pinyin)) in self.text.raw_text.iter().zip(self.text.pinyin_text.iter()).enumerate() {
-            let line = line.into_iter().collect::<String>();
-            execute!(
-                self.stdout, 
-                MoveTo(self.text.pos[idx].col, self.text.pos[idx].row-1),
-                Print(&pinyin),
-                MoveTo(self.text.pos[idx].col, self.text.pos[idx].row),
-                Print(&line)
-            )?;
-        }
+        self.draw_text()?;
         self.init_footer()?;
-        execute!(self.stdout, MoveTo(self.cursor_col, self.cursor_row))?;
+        self.backend.move_to(self.cursor_col, self.cursor_row)?;
         Ok(())
     }
 
 
     fn init_footer(&mut self) -> Result<()> {
         let cursor_rows = self.rows - 1;
-        let cursor_cols = (self.cols - 49) / 2;
-        let restart = "ctrl-r"
-        .with(Color::Blue)
-        .attribute(Attribute::Bold);
-
-        let quit = "ESC"
-        .with(Color::Blue)
-        .attribute(Attribute::Bold);
-
-        let next = "ctrl-n"
-        .with(Color::Blue)
-        .attribute(Attribute::Bold);
-
-        execute!(self.stdout,
-            MoveTo(cursor_cols, cursor_rows),
-            Print(restart),
-            Print(" to restart, "),
-            Print(next),
-            Print(" to next, "),
-            Print(quit),
-            Print(" to quit"),
-        )?;
+        // 窗口太窄时省略排在后面的按键，两侧留出边框
+        let mut length = 0;
+        let mut count = 0;
+        for (key, describe) in self.footer.iter() {
+            let width = key.width() + describe.len() + 6;
+            if length + width - 2 > (self.cols as usize).saturating_sub(2) {
+                break;
+            }
+            length += width;
+            count += 1;
+        }
+        let cursor_cols = self.cols.saturating_sub(length.saturating_sub(2) as u16) / 2;
+        self.backend.move_to(cursor_cols, cursor_rows)?;
+        for (idx, (key, describe)) in self.footer[..count].iter().enumerate() {
+            if idx > 0 {
+                self.backend.print(", ")?;
+            }
+            self.backend.print_styled(self.theme.key.apply(key))?;
+            self.backend.print(" to ")?;
+            self.backend.print(describe)?;
+        }
         Ok(())
     }
 
-    fn get_cur_text_line(&mut self) -> u16 {
-        if self.text.pos.len() > 1 {
-            (self.cursor_row - self.text.pos[0].row) / (self.text.pos[1].row - self.text.pos[0].row)
-        } else {
-            0
-        }
+    /// 当前行第 idx 个字符所在的列，idx 等于行长度时为行尾之后的一列
+    fn column_at(&self, line: usize, idx: usize) -> u16 {
+        let columns = &self.text.lines.columns[line];
+        let offset = match columns.get(idx) {
+            Some(col) => *col,
+            None => match (columns.last(), self.text.lines.raw_text[line].last()) {
+                (Some(col), Some(ch)) => col + ch.width().unwrap_or(1) as u16,
+                _ => 0,
+            },
+        };
+        self.text.pos[line].col + offset
     }
 
-    pub fn display_c(&mut self, ch: &char) -> Result<bool>{
-        let cur_line = self.get_cur_text_line() as usize;
-        let line_pos = &self.text.pos[cur_line];
+    /// 显示第 line 行第 idx 个位置输入的字符
+    pub fn display_typed(&mut self, line: usize, idx: usize, ch: char) -> Result<()> {
+        let styled = self.styled(self.text.lines.raw_text[line][idx], ch);
+        let col = self.column_at(line, idx);
+        self.backend.move_to(col, self.text.pos[line].row)?;
+        self.backend.print_styled(styled)?;
+        Ok(())
+    }
 
-        if is_chinese(ch) || transform_punctuation(ch).is_some(){
-            return Err(anyhow::Error::msg("请不要开启中文输入法！"));
-        }
-        // 获取所在行的 位置信息
-
-        let mut stylize_ch = ch.attribute(Attribute::Bold);
-        // 获取当前游标所在行的位置
-        if self.cursor_col - line_pos.col < self.text.raw_text[cur_line].len() as u16 {
-            self.input.push(ch.clone());
-            let raw_idx = self.cursor_col - line_pos.col;
-            let equal = self.text.raw_text[cur_line][raw_idx as usize] == *ch;
-            self.cursor_col += 1;
-            if equal {
-                stylize_ch = stylize_ch.with(Color::Green);
-                execute!(self.stdout, Print(stylize_ch))?; 
-                self.stdout.flush()?;
-                return Ok(true)
-            } else {
-                stylize_ch = stylize_ch.with(Color::Red);
-                execute!(self.stdout, Print(stylize_ch))?; 
-                self.stdout.flush()?;
-                return Ok(false)
-            }
-        }
-        Ok(false)
+    /// 删除输入后恢复显示原字符
+    pub fn display_erased(&mut self, line: usize, idx: usize) -> Result<()> {
+        let ch = self.text.lines.raw_text[line][idx];
+        let col = self.column_at(line, idx);
+        self.backend.move_to(col, self.text.pos[line].row)?;
+        self.backend.print(ch)?;
+        Ok(())
     }
-    
-    pub fn display_with_backspace(&mut self ) -> Result<i8> {
-        let cur_line = self.get_cur_text_line() as usize;
-        let line_pos = &self.text.pos[cur_line];
-        let raw_idx = self.cursor_col - line_pos.col;
-
-        if raw_idx > 0 {
-            self.cursor_col -= 1;
-            let old_ch = self.input.pop().unwrap();
-            let ch = self.text.raw_text[cur_line][(raw_idx - 1) as usize];
-
-            execute!(self.stdout, cursor::MoveLeft(1), Print(ch), cursor::MoveLeft(1))?;
-            self.stdout.flush()?;
-            if ch == old_ch {
-                return Ok(-1);
-            } else {
-                return Ok(1);
-            }
-        } 
-         if cur_line != 0{
-            self.cursor_col = self.text.pos[cur_line-1].col + self.text.raw_text[cur_line-1].len() as u16;
-            self.cursor_row = self.text.pos[cur_line-1].row;
-            execute!(self.stdout, MoveTo(self.cursor_col, self.cursor_row))?;
-        }
 
-        self.stdout.flush()?;
-        Ok(0)
-    }
-
-    pub fn move_next_line(&mut self ) -> Result<(bool, bool)>{
-        let cur_line = self.get_cur_text_line() as usize;
-        match self.display_c(&'↵') {
-            Ok(m) => {
-                let next_line = cur_line + 1;
-                if m && next_line < self.text.raw_text.len() {
-                    self.cursor_col = self.text.pos[next_line].col;
-                    self.cursor_row = self.text.pos[next_line].row;
-                    execute!(self.stdout, MoveTo(self.cursor_col, self.cursor_row))?;
-                } else if next_line >= self.text.raw_text.len() && self.text.pos[cur_line].col + self.text.raw_text[cur_line].len() as u16 == self.cursor_col {
-                    return Ok((true, m))
-                }
-                Ok((false, m))
-            },
-            Err(e) => Err(e),
-        }
+    pub fn move_cursor(&mut self, line: usize, idx: usize) -> Result<()> {
+        self.cursor_row = self.text.pos[line].row;
+        self.cursor_col = self.column_at(line, idx);
+        self.backend.move_to(self.cursor_col, self.cursor_row)?;
+        self.backend.flush()?;
+        Ok(())
     }
     
+    /// 这一轮练习的字符数，包括之前的页
     pub fn count_char(&self) -> usize{
-        self.text.raw_text.iter().fold(0, |sum, line| sum + line.len())
+        self.text.lines.raw_text.iter().fold(self.text.previous_chars, |sum, line| sum + line.len())
     }
 }
 
-impl Drop for Tui {
+impl<B: Backend> Drop for Tui<B> {
     fn drop(&mut self) {
-        terminal::disable_raw_mode().unwrap();
-        execute!(self.stdout, Clear(terminal::ClearType::All), MoveTo(0,0)).unwrap();
+        self.backend.disable_raw_mode().unwrap();
+        self.backend.clear().unwrap();
+        self.backend.move_to(0, 0).unwrap();
     }
 }
 
 #[cfg(test)]
 mod test {
 
-    use crate::textgen;
-
     use super::*;
+    use crate::backend::BufferBackend;
+
     #[test]
     fn test_init() {
 
         let mut tg = textgen::TextGenerator::new();
-        tg.read_content("./text/en.txt").unwrap();
+        tg.load_content(include_str!("../text/en.txt"));
         let iter = tg.into_iter();
         // let text = iter.next().unwrap();
-        let mut tui = Tui::new(iter).unwrap();
+        let mut tui = Tui::new(iter, BufferBackend::new(80, 24)).unwrap();
         tui.init().unwrap();
-        println!("{}", tui.count_char());
-        assert_eq!(tui.count_char(), 100);
+        assert!(tui.backend.raw_mode);
+        assert_eq!(tui.text.lines.raw_text.len(), 6);
+        for (line, pos) in tui.text.lines.target_text.iter().zip(tui.text.pos.iter()) {
+            let shown: String = tui.backend.line(pos.row).chars().skip(pos.col as usize).collect();
+            assert!(shown.starts_with(line.as_str()));
+        }
+        assert!(tui.backend.line(23).contains("ctrl-r to restart, ctrl-n to next, ESC to quit"));
+        assert_eq!(tui.backend.cursor, (tui.text.pos[0].col, tui.text.pos[0].row));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_resize() {
+        use std::time::Instant;
+        use typlap::session::Input;
+        let mut tg = textgen::TextGenerator::new();
+        tg.load_content(&["abcdefghijklm"; 10].join(" "));
+        let mut tui = Tui::new(tg.into_iter(), BufferBackend::new(50, 24)).unwrap();
+        tui.set_length(Some(3));
+        tui.init().unwrap();
+        assert_eq!(tui.text.lines.raw_text.len(), 2);
+        let mut session = Session::new(tui.text.lines.raw_text.clone());
+        for ch in "abcdefghijklm abcdefghijklm\nabc".chars() {
+            let input = if ch == '\n' { Input::Enter } else { Input::Char(ch) };
+            session.handle(input, Instant::now());
+        }
+
+        // 变宽后三个词排在一行，已经输入的回车换成空格
+        tui.backend.set_size(80, 24);
+        tui.set_size(80, 24);
+        tui.resize(&mut session).unwrap();
+        assert_eq!(tui.text.lines.raw_text.len(), 1);
+        assert_eq!(session.input[0].iter().collect::<String>(), "abcdefghijklm abcdefghijklm abc");
+        assert_eq!(session.cursor(), (0, 31));
+        assert_eq!(tui.backend.cursor, (tui.text.pos[0].col + 31, tui.text.pos[0].row));
+        assert_eq!(session.evaluator.final_chars_typed_correctly, 31);
+    }
+
+    #[test]
+    fn test_too_small() {
+        let tui = |cols, rows, content: &str| {
+            let mut tg = textgen::TextGenerator::new();
+            tg.load_content(content);
+            let mut tui = Tui::new(tg.into_iter(), BufferBackend::new(cols, rows)).unwrap();
+            tui.set_length(Some(2));
+            tui.init().unwrap();
+            tui
+        };
+        let t = tui(40, 24, "abc abc abc");
+        assert!(t.backend.text().contains("width 40, need at least 50 columns"));
+        let t = tui(80, 6, "abc abc abc");
+        assert!(t.backend.text().contains("height 6, need at least 7 rows"));
+
+        // 一个词连同行尾的 ↵ 和两侧边框比窗口还宽
+        let word = "a".repeat(50);
+        let mut t = tui(80, 24, &[word.as_str(); 3].join(" "));
+        assert!(!t.too_small());
+        let mut session = Session::new(t.text.lines.raw_text.clone());
+        t.backend.set_size(50, 24);
+        t.set_size(50, 24);
+        t.resize(&mut session).unwrap();
+        assert!(t.backend.text().contains("width 50, the text needs 53 columns"));
+    }
+
+    #[test]
+    fn test_skipped() {
+        let mut tg = textgen::TextGenerator::new();
+        // 部首 ⺀ 没有粤语读音
+        tg.load_content("风花 雪月 ⺀月 花月");
+        let mut tui = Tui::new(tg.into_iter(), BufferBackend::new(80, 24)).unwrap();
+        tui.set_scheme(Scheme::Jyutping);
+        tui.init().unwrap();
+        assert_eq!(tui.text.skipped, vec![String::from("⺀月")]);
+        assert_eq!(tui.text.lines.raw_text.iter().flatten().filter(|ch| **ch == ' ').count(), 2);
+    }
+}
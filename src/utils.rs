@@ -1,74 +1,690 @@
 pub mod util {
-    use std::{collections::HashMap, lazy::SyncOnceCell};
+    use std::{collections::HashMap, sync::OnceLock};
 
     // use jieba_rs::Jieba;
-    use pinyin::ToPinyin;
+    use pinyin::{ToPinyin, ToPinyinMulti};
+    use serde::Deserialize;
+
+    // pub static TOKENIZER: OnceLock<Jieba> = OnceLock::new();  
+    pub static TRANSFORM_PUNCTUATION: OnceLock<Punctuation> = OnceLock::new();
+    pub static ZHUYIN_FINALS: OnceLock<HashMap<&'static str, &'static str>> = OnceLock::new();
+    pub static ZHUYIN_KEYS: OnceLock<HashMap<char, char>> = OnceLock::new();
+    pub static JYUTPING: OnceLock<HashMap<char, &'static str>> = OnceLock::new();
+    pub static ROMAJI: OnceLock<HashMap<String, String>> = OnceLock::new();
+
+    /// 内置的粤拼读音表，用 examples/jyutping_table.rs 从 Unihan kCantonese 生成
+    const JYUTPING_TABLE: &str = include_str!("../dict/jyutping.txt");
+
+    const ZHUYIN_INITIALS: [(&str, &str); 21] = [
+        ("zh", "ㄓ"), ("ch", "ㄔ"), ("sh", "ㄕ"),
+        ("b", "ㄅ"), ("p", "ㄆ"), ("m", "ㄇ"), ("f", "ㄈ"),
+        ("d", "ㄉ"), ("t", "ㄊ"), ("n", "ㄋ"), ("l", "ㄌ"),
+        ("g", "ㄍ"), ("k", "ㄎ"), ("h", "ㄏ"),
+        ("j", "ㄐ"), ("q", "ㄑ"), ("x", "ㄒ"),
+        ("r", "ㄖ"), ("z", "ㄗ"), ("c", "ㄘ"), ("s", "ㄙ"),
+    ];
+
+    /// 输入方案
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+    #[serde(rename_all = "kebab-case")]
+    pub enum Scheme {
+        /// 汉语拼音（不带声调）
+        Pinyin,
+        /// 注音符号，按大千键盘布局输入
+        #[serde(alias = "bopomofo")]
+        Zhuyin,
+        /// 粤拼（不带声调）
+        #[serde(alias = "cantonese")]
+        Jyutping,
+    }
+
+    impl std::str::FromStr for Scheme {
+        type Err = anyhow::Error;
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            match s {
+                "pinyin" => Ok(Scheme::Pinyin),
+                "zhuyin" | "bopomofo" => Ok(Scheme::Zhuyin),
+                "jyutping" | "cantonese" => Ok(Scheme::Jyutping),
+                _ => Err(anyhow::Error::msg(format!("未知的输入方案: {}", s))),
+            }
+        }
+    }
 
-    // pub static TOKENIZER: SyncOnceCell<Jieba> = SyncOnceCell::new();  
-    pub static TRANSFORM_PUNCTUATION: SyncOnceCell<HashMap<char, char>> = SyncOnceCell::new();
-    
     /// 判断字符是否是中文
     pub fn is_chinese(cp: &char) -> bool {
-        match *cp {
+        matches!(*cp,
             '\u{4E00}'..='\u{9FFF}' | 
             '\u{2E80}'..='\u{2EFF}' | 
             '\u{31C0}' ..= '\u{31EF}' |
             '\u{2F00}' ..= '\u{2FFF}' | 
             '\u{3200}' ..= '\u{32FF}' |
-            '\u{F900}' ..= '\u{FAFF}' => true,
-            _ => false,
+            '\u{F900}' ..= '\u{FAFF}'
+        )
+    }
+
+    /// 判断字符是否是假名（平假名、片假名以及长音符）
+    pub fn is_kana(cp: &char) -> bool {
+        matches!(*cp, '\u{3041}'..='\u{3096}' | '\u{309D}'..='\u{309E}' | '\u{30A1}'..='\u{30FA}' | '\u{30FC}'..='\u{30FE}')
+    }
+
+    /// 中文标点转换表
+    pub struct Punctuation {
+        pub table: HashMap<String, String>,
+        /// 需要输入全角标点，不做转换
+        pub full_width: bool,
+        /// 转换表中最长的键包含的字符数
+        max_len: usize,
+    }
+
+    impl Punctuation {
+        pub fn new(table: HashMap<String, String>, full_width: bool) -> Self {
+            let max_len = table.keys().map(|k| k.chars().count()).max().unwrap_or(1);
+            Self { table, full_width, max_len }
+        }
+    }
+
+    /// 默认的标点转换表，输入方式与中文输入法一致
+    pub fn default_punctuation() -> HashMap<String, String> {
+        let mut map = HashMap::new();
+        map.insert('。', '.');
+        map.insert('，', ',');
+        map.insert('！', '!');
+        map.insert('‘', '\'');
+        map.insert('’', '\'');
+        map.insert('；',';');
+        map.insert('：', ':');
+        map.insert('“', '\"');
+        map.insert('”', '\"');
+        map.insert('、', ',');
+        map.insert('《', '<');
+        map.insert('》', '>');
+        map.insert('〈', '<');
+        map.insert('〉', '>');
+        map.insert('？', '?');
+        map.insert('（', '(');
+        map.insert('）', ')');
+        map.insert('【', '[');
+        map.insert('】', ']');
+        map.insert('「', '"');
+        map.insert('」', '"');
+        map.insert('『', '\'');
+        map.insert('』', '\'');
+        map.insert('〔', '[');
+        map.insert('〕', ']');
+        map.insert('～', '~');
+        map.insert('￥', '$');
+        // 全角的 ASCII 字符，例如 ＡＢＣ１２３
+        for cp in '\u{FF01}'..='\u{FF5E}' {
+            map.entry(cp).or_insert(char::from_u32(cp as u32 - 0xFEE0).unwrap());
         }
+        map.into_iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    /// 破折号、省略号和间隔号在英文中也会用到，只在前后有中文时转换
+    const CJK_CONTEXT_PUNCTUATION: [(&str, &str); 5] = [("……", "..."), ("…", "..."), ("——", "--"), ("—", "-"), ("·", ".")];
+
+    /// 使用配置文件中的转换表覆盖默认值，需要在第一次转换之前调用
+    pub fn init_punctuation(table: &HashMap<String, String>, full_width: bool) {
+        let mut map = default_punctuation();
+        map.extend(table.iter().map(|(k, v)| (k.clone(), v.clone())));
+        let _ = TRANSFORM_PUNCTUATION.set(Punctuation::new(map, full_width));
+    }
+
+    fn punctuation() -> &'static Punctuation {
+        TRANSFORM_PUNCTUATION.get_or_init(|| Punctuation::new(default_punctuation(), false))
+    }
+
+    /// 判断字符是否是转换表中的中文标点
+    pub fn is_punctuation(cp: &char) -> bool {
+        punctuation().table.contains_key(&cp.to_string())
+    }
+
+    /// 匹配字符串开头的中文标点，优先匹配最长的标点，返回 (匹配的字节数, 转换结果)
+    /// 需要输入全角标点时不做转换
+    pub fn match_punctuation(s: &str) -> Option<(usize, &'static str)> {
+        let punctuation = punctuation();
+        if punctuation.full_width {
+            return None;
+        }
+        let ends: Vec<usize> = s.char_indices().map(|(idx, cp)| idx + cp.len_utf8()).take(punctuation.max_len).collect();
+        ends.into_iter().rev().find_map(|end| punctuation.table.get(&s[..end]).map(|res| (end, res.as_str())))
+    }
+
+    /// 匹配字符串开头的破折号或省略号，prev 或其后的字符是汉字或中文标点时才转换
+    pub fn match_cjk_punctuation(s: &str, prev: Option<char>) -> Option<(usize, &'static str)> {
+        if punctuation().full_width {
+            return None;
+        }
+        let is_cjk = |cp: char| is_chinese(&cp) || is_punctuation(&cp);
+        let (key, res) = CJK_CONTEXT_PUNCTUATION.iter().find(|(key, _)| s.starts_with(key))?;
+        let next = s[key.len()..].chars().next();
+        prev.into_iter().chain(next).any(is_cjk).then_some((key.len(), *res))
     }
 
     // 将常见的中文标点转换为英文标点
-    pub fn transform_punctuation(cp: &char) -> Option<u8>{
-        let map = TRANSFORM_PUNCTUATION.get_or_init(|| {
-            let mut map = HashMap::new();
-            map.insert('。', '.');
-            map.insert('，', ',');
-            map.insert('！', '!');
-            map.insert('‘', '\'');
-            map.insert('’', '\'');
-            map.insert('；',';');
-            map.insert('：', ':'); 
-            map.insert('“', '\"');
-            map.insert('”', '\"');
-            map.insert('、', '\\');
-            map.insert('《', '<');
-            map.insert('》', '>');
-            map.insert('？', '?');
-            map.insert('（', '(');
-            map.insert('）', ')');
-            map
-        });
-        map.get(cp).map(|v| *v as u8)
+    pub fn transform_punctuation(cp: &char) -> Option<&'static str> {
+        let s = cp.to_string();
+        match_punctuation(&s).filter(|(len, _)| *len == s.len()).map(|(_, res)| res)
     }
+
     pub fn transform_pinyin(cp: &char) -> Vec<u8> {
         cp.to_pinyin().map(|pinyin| {
-            pinyin.plain().as_bytes().into_iter().map(|&b| b).collect()
+            pinyin.plain().as_bytes().to_vec()
         }).unwrap()
     }
 
     pub fn transform(s: &str) -> (usize, String) {
-        let mut res: Vec<u8> = Vec::new(); 
+        let mut res = String::new();
         let mut chinese_cnt = 0;
-        s.chars().for_each(|cp| {
-            match is_chinese(&cp) {
-                true => {
-                    res.append(&mut transform_pinyin(&cp));
+        let mut rest = s;
+        let mut prev = None;
+        while let Some(cp) = rest.chars().next() {
+            if is_chinese(&cp) {
+                if let Some(pinyin) = cp.to_pinyin() {
+                    res.push_str(pinyin.plain());
+                }
+                chinese_cnt += 1;
+                rest = &rest[cp.len_utf8()..];
+            } else if let Some((len, c)) = match_punctuation(rest).or_else(|| match_cjk_punctuation(rest, prev)) {
+                res.push_str(c);
+                chinese_cnt += rest[..len].chars().count();
+                prev = rest[..len].chars().last();
+                rest = &rest[len..];
+                continue;
+            } else {
+                // 需要输入全角标点时，标点原样保留
+                if is_punctuation(&cp) {
                     chinese_cnt += 1;
                 }
-                false => {
-                    if let Some(c) = transform_punctuation(&cp) {
-                        res.push(c);
-                        chinese_cnt += 1;
-                    } else {
-                        res.push(cp as u8);
-                    }
+                res.push(cp);
+                rest = &rest[cp.len_utf8()..];
+            }
+            prev = Some(cp);
+        }
+        (chinese_cnt, res)
+    }
+
+    /// 词中的汉字数，不计标点、全角字符和 `|`、`{}` 标注中的读音
+    pub fn count_chinese(word: &str) -> usize {
+        if !word.contains('{') {
+            let text = word.split_once('|').map_or(word, |(text, _)| text);
+            return text.chars().filter(is_chinese).count();
+        }
+        let mut text = String::new();
+        let mut rest = word;
+        while let Some((plain, ruby)) = rest.split_once('{') {
+            text.push_str(plain);
+            let (annotation, remain) = ruby.split_once('}').unwrap_or((ruby, ""));
+            text.push_str(annotation.split_once('|').map_or(annotation, |(text, _)| text));
+            rest = remain;
+        }
+        text.push_str(rest);
+        text.chars().filter(is_chinese).count()
+    }
+
+    /// 常见非 ASCII 字符对应的 ASCII 输入，去掉变音符号，例如 é -> e、ß -> ss、— -> -
+    pub fn ascii_fallback(cp: &char) -> Option<&'static str> {
+        let res = match *cp {
+            'à' | 'á' | 'â' | 'ã' | 'ä' | 'å' | 'ā' | 'ă' | 'ą' => "a",
+            'À' | 'Á' | 'Â' | 'Ã' | 'Ä' | 'Å' | 'Ā' | 'Ă' | 'Ą' => "A",
+            'ç' | 'ć' | 'ĉ' | 'ċ' | 'č' => "c",
+            'Ç' | 'Ć' | 'Ĉ' | 'Ċ' | 'Č' => "C",
+            'ď' | 'đ' | 'ð' => "d",
+            'Ď' | 'Đ' | 'Ð' => "D",
+            'è' | 'é' | 'ê' | 'ë' | 'ē' | 'ĕ' | 'ė' | 'ę' | 'ě' => "e",
+            'È' | 'É' | 'Ê' | 'Ë' | 'Ē' | 'Ĕ' | 'Ė' | 'Ę' | 'Ě' => "E",
+            'ĝ' | 'ğ' | 'ġ' | 'ģ' => "g",
+            'Ĝ' | 'Ğ' | 'Ġ' | 'Ģ' => "G",
+            'ì' | 'í' | 'î' | 'ï' | 'ī' | 'ĭ' | 'į' | 'ı' => "i",
+            'Ì' | 'Í' | 'Î' | 'Ï' | 'Ī' | 'Ĭ' | 'Į' | 'İ' => "I",
+            'ł' | 'ĺ' | 'ļ' | 'ľ' => "l",
+            'Ł' | 'Ĺ' | 'Ļ' | 'Ľ' => "L",
+            'ñ' | 'ń' | 'ņ' | 'ň' => "n",
+            'Ñ' | 'Ń' | 'Ņ' | 'Ň' => "N",
+            'ò' | 'ó' | 'ô' | 'õ' | 'ö' | 'ø' | 'ō' | 'ŏ' | 'ő' => "o",
+            'Ò' | 'Ó' | 'Ô' | 'Õ' | 'Ö' | 'Ø' | 'Ō' | 'Ŏ' | 'Ő' => "O",
+            'ŕ' | 'ŗ' | 'ř' => "r",
+            'Ŕ' | 'Ŗ' | 'Ř' => "R",
+            'ś' | 'ŝ' | 'ş' | 'š' => "s",
+            'Ś' | 'Ŝ' | 'Ş' | 'Š' => "S",
+            'ţ' | 'ť' => "t",
+            'Ţ' | 'Ť' => "T",
+            'ù' | 'ú' | 'û' | 'ü' | 'ū' | 'ŭ' | 'ů' | 'ű' | 'ų' => "u",
+            'Ù' | 'Ú' | 'Û' | 'Ü' | 'Ū' | 'Ŭ' | 'Ů' | 'Ű' | 'Ų' => "U",
+            'ý' | 'ÿ' => "y",
+            'Ý' | 'Ÿ' => "Y",
+            'ź' | 'ż' | 'ž' => "z",
+            'Ź' | 'Ż' | 'Ž' => "Z",
+            'æ' => "ae",
+            'Æ' => "AE",
+            'œ' => "oe",
+            'Œ' => "OE",
+            'ß' => "ss",
+            'þ' => "th",
+            'Þ' => "TH",
+            '‐' | '‑' | '‒' | '–' | '—' | '―' => "-",
+            '‘' | '’' | '‚' | '‹' | '›' => "'",
+            '“' | '”' | '„' | '«' | '»' => "\"",
+            '…' => "...",
+            '¡' => "!",
+            '¿' => "?",
+            '\u{A0}' => " ",
+            _ => return None,
+        };
+        Some(res)
+    }
+
+    /// 将字符串中的非 ASCII 字符替换为对应的 ASCII 输入，没有对应关系的字符保持不变
+    pub fn to_ascii(s: &str) -> String {
+        s.chars().fold(String::new(), |mut res, cp| {
+            match ascii_fallback(&cp) {
+                Some(fallback) => res.push_str(fallback),
+                None => res.push(cp),
+            }
+            res
+        })
+    }
+
+    /// 将不带声调的拼音转换为注音符号
+    pub fn pinyin_to_zhuyin(pinyin: &str) -> String {
+        let finals = ZHUYIN_FINALS.get_or_init(|| {
+            let mut map = HashMap::new();
+            map.insert("a", "ㄚ");
+            map.insert("o", "ㄛ");
+            map.insert("e", "ㄜ");
+            map.insert("ê", "ㄝ");
+            map.insert("ai", "ㄞ");
+            map.insert("ei", "ㄟ");
+            map.insert("ao", "ㄠ");
+            map.insert("ou", "ㄡ");
+            map.insert("an", "ㄢ");
+            map.insert("en", "ㄣ");
+            map.insert("ang", "ㄤ");
+            map.insert("eng", "ㄥ");
+            map.insert("er", "ㄦ");
+            map.insert("ong", "ㄨㄥ");
+            map.insert("i", "ㄧ");
+            map.insert("ia", "ㄧㄚ");
+            map.insert("io", "ㄧㄛ");
+            map.insert("ie", "ㄧㄝ");
+            map.insert("iao", "ㄧㄠ");
+            map.insert("iou", "ㄧㄡ");
+            map.insert("ian", "ㄧㄢ");
+            map.insert("in", "ㄧㄣ");
+            map.insert("iang", "ㄧㄤ");
+            map.insert("ing", "ㄧㄥ");
+            map.insert("iong", "ㄩㄥ");
+            map.insert("u", "ㄨ");
+            map.insert("ua", "ㄨㄚ");
+            map.insert("uo", "ㄨㄛ");
+            map.insert("uai", "ㄨㄞ");
+            map.insert("uei", "ㄨㄟ");
+            map.insert("uan", "ㄨㄢ");
+            map.insert("uen", "ㄨㄣ");
+            map.insert("uang", "ㄨㄤ");
+            map.insert("ueng", "ㄨㄥ");
+            map.insert("ü", "ㄩ");
+            map.insert("üe", "ㄩㄝ");
+            map.insert("üan", "ㄩㄢ");
+            map.insert("ün", "ㄩㄣ");
+            // 嗯、呣 等没有韵母的叹词
+            map.insert("n", "ㄣ");
+            map.insert("ng", "ㄣ");
+            map.insert("m", "ㄇ");
+            map
+        });
+
+        if let Some(zy) = finals.get(pinyin) {
+            return zy.to_string();
+        }
+
+        let (initial, rest) = match ZHUYIN_INITIALS.iter().find(|(py, _)| pinyin.starts_with(py) && pinyin.len() > py.len()) {
+            Some((py, zy)) => (*zy, &pinyin[py.len()..]),
+            None => ("", pinyin),
+        };
+
+        // zhi chi shi ri zi ci si 只写声母
+        if rest == "i" && matches!(initial, "ㄓ" | "ㄔ" | "ㄕ" | "ㄖ" | "ㄗ" | "ㄘ" | "ㄙ") {
+            return String::from(initial);
+        }
+
+        // 还原 y、w 开头以及 iu、ui、un 等缩写形式
+        let rest = if initial.is_empty() {
+            match rest {
+                "yi" | "yin" | "ying" => rest[1..].to_string(),
+                "wu" => String::from("u"),
+                _ if rest.starts_with("yu") => format!("ü{}", &rest[2..]),
+                _ if rest.starts_with('y') => format!("i{}", &rest[1..]),
+                _ if rest.starts_with('w') => format!("u{}", &rest[1..]),
+                _ => rest.to_string(),
+            }
+        } else if matches!(initial, "ㄐ" | "ㄑ" | "ㄒ") && rest.starts_with('u') {
+            format!("ü{}", &rest[1..])
+        } else {
+            match rest {
+                "iu" => String::from("iou"),
+                "ui" => String::from("uei"),
+                "un" => String::from("uen"),
+                _ => rest.to_string(),
+            }
+        };
+
+        match finals.get(rest.as_str()) {
+            Some(zy) => format!("{}{}", initial, zy),
+            None => pinyin.to_string(),
+        }
+    }
+
+    /// 注音符号在大千键盘布局上对应的按键
+    pub fn zhuyin_key(cp: &char) -> Option<char> {
+        let map = ZHUYIN_KEYS.get_or_init(|| {
+            let layout = [
+                ('ㄅ', '1'), ('ㄉ', '2'), ('ㄓ', '5'), ('ㄚ', '8'), ('ㄞ', '9'), ('ㄢ', '0'), ('ㄦ', '-'),
+                ('ㄆ', 'q'), ('ㄊ', 'w'), ('ㄍ', 'e'), ('ㄐ', 'r'), ('ㄔ', 't'),
+                ('ㄗ', 'y'), ('ㄧ', 'u'), ('ㄛ', 'i'), ('ㄟ', 'o'), ('ㄣ', 'p'),
+                ('ㄇ', 'a'), ('ㄋ', 's'), ('ㄎ', 'd'), ('ㄑ', 'f'), ('ㄕ', 'g'),
+                ('ㄘ', 'h'), ('ㄨ', 'j'), ('ㄜ', 'k'), ('ㄠ', 'l'), ('ㄤ', ';'),
+                ('ㄈ', 'z'), ('ㄌ', 'x'), ('ㄏ', 'c'), ('ㄒ', 'v'), ('ㄖ', 'b'),
+                ('ㄙ', 'n'), ('ㄩ', 'm'), ('ㄝ', ','), ('ㄡ', '.'), ('ㄥ', '/'),
+            ];
+            layout.into_iter().collect()
+        });
+        map.get(cp).copied()
+    }
+
+    /// 注音的声调符号及其在大千键盘上的声调键，按一至四声、轻声排列，一声用空格键
+    const ZHUYIN_TONES: [(&str, char); 5] = [("", ' '), ("ˊ", '6'), ("ˇ", '3'), ("ˋ", '4'), ("˙", '7')];
+
+    /// 将声调数字在末尾的拼音（例如 zhong1，轻声不带数字）转换为 (带声调的注音符号, 大千键盘上的按键序列)。
+    /// 轻声符号标在音节前，其余标在音节后；按键序列以声调键结束
+    pub fn zhuyin_with_tone(pinyin: &str) -> (String, String) {
+        let (plain, tone) = match pinyin.chars().last().and_then(|cp| cp.to_digit(10)) {
+            Some(tone @ 1..=4) => (&pinyin[..pinyin.len() - 1], tone as usize),
+            _ => (pinyin, 5),
+        };
+        let zhuyin = pinyin_to_zhuyin(plain);
+        let (mark, key) = ZHUYIN_TONES[tone - 1];
+        let mut keys: String = zhuyin.chars().map(|c| zhuyin_key(&c).unwrap_or(c)).collect();
+        keys.push(key);
+        let hint = if tone == 5 { format!("{}{}", mark, zhuyin) } else { format!("{}{}", zhuyin, mark) };
+        (hint, keys)
+    }
+
+    /// 返回汉字的注音符号及其在大千键盘上的按键序列（含声调键）
+    pub fn transform_zhuyin(cp: &char) -> Option<(String, String)> {
+        Some(zhuyin_with_tone(cp.to_pinyin()?.with_tone_num_end()))
+    }
+
+    /// 多音字中与不带声调的读音一致的拼音，声调数字在末尾
+    fn pinyin_with_tone(cp: &char, reading: &str) -> Option<&'static str> {
+        cp.to_pinyin_multi()?.into_iter().find(|pinyin| pinyin.plain() == reading).map(|pinyin| pinyin.with_tone_num_end())
+    }
+
+    /// 查询汉字的粤拼（不带声调），读音表中没有的字返回 None
+    pub fn transform_jyutping(cp: &char) -> Option<String> {
+        let map = JYUTPING.get_or_init(|| {
+            JYUTPING_TABLE
+                .lines()
+                .filter(|line| !line.starts_with('#'))
+                .filter_map(|line| {
+                    let mut it = line.split_whitespace();
+                    Some((it.next()?.chars().next()?, it.next()?))
+                })
+                .collect()
+        });
+        map.get(cp).map(|jyutping| jyutping.trim_end_matches(|c: char| c.is_ascii_digit()).to_string())
+    }
+
+    /// 将假名转换为平文式（Hepburn）罗马字，长音按假名拼写输入，例如 とうきょう -> toukyou
+    pub fn kana_to_romaji(s: &str) -> String {
+        let map = ROMAJI.get_or_init(|| {
+            let table = [
+                ("あ", "a"), ("い", "i"), ("う", "u"), ("え", "e"), ("お", "o"),
+                ("ぁ", "a"), ("ぃ", "i"), ("ぅ", "u"), ("ぇ", "e"), ("ぉ", "o"),
+                ("か", "ka"), ("き", "ki"), ("く", "ku"), ("け", "ke"), ("こ", "ko"),
+                ("が", "ga"), ("ぎ", "gi"), ("ぐ", "gu"), ("げ", "ge"), ("ご", "go"),
+                ("さ", "sa"), ("し", "shi"), ("す", "su"), ("せ", "se"), ("そ", "so"),
+                ("ざ", "za"), ("じ", "ji"), ("ず", "zu"), ("ぜ", "ze"), ("ぞ", "zo"),
+                ("た", "ta"), ("ち", "chi"), ("つ", "tsu"), ("て", "te"), ("と", "to"),
+                ("だ", "da"), ("ぢ", "ji"), ("づ", "zu"), ("で", "de"), ("ど", "do"),
+                ("な", "na"), ("に", "ni"), ("ぬ", "nu"), ("ね", "ne"), ("の", "no"),
+                ("は", "ha"), ("ひ", "hi"), ("ふ", "fu"), ("へ", "he"), ("ほ", "ho"),
+                ("ば", "ba"), ("び", "bi"), ("ぶ", "bu"), ("べ", "be"), ("ぼ", "bo"),
+                ("ぱ", "pa"), ("ぴ", "pi"), ("ぷ", "pu"), ("ぺ", "pe"), ("ぽ", "po"),
+                ("ま", "ma"), ("み", "mi"), ("む", "mu"), ("め", "me"), ("も", "mo"),
+                ("や", "ya"), ("ゆ", "yu"), ("よ", "yo"), ("ゃ", "ya"), ("ゅ", "yu"), ("ょ", "yo"),
+                ("ら", "ra"), ("り", "ri"), ("る", "ru"), ("れ", "re"), ("ろ", "ro"),
+                ("わ", "wa"), ("ゎ", "wa"), ("ゐ", "i"), ("ゑ", "e"), ("を", "o"), ("ん", "n"),
+                ("ゔ", "vu"), ("ゕ", "ka"), ("ゖ", "ke"),
+                // 外来语中常见的组合
+                ("しぇ", "she"), ("ちぇ", "che"), ("じぇ", "je"),
+                ("てぃ", "ti"), ("でぃ", "di"), ("とぅ", "tu"), ("どぅ", "du"),
+                ("ふぁ", "fa"), ("ふぃ", "fi"), ("ふぇ", "fe"), ("ふぉ", "fo"),
+                ("うぃ", "wi"), ("うぇ", "we"), ("うぉ", "wo"),
+                ("ゔぁ", "va"), ("ゔぃ", "vi"), ("ゔぇ", "ve"), ("ゔぉ", "vo"),
+                ("つぁ", "tsa"), ("つぃ", "tsi"), ("つぇ", "tse"), ("つぉ", "tso"),
+            ];
+            let mut map: HashMap<String, String> = table.iter().map(|(k, r)| (k.to_string(), r.to_string())).collect();
+            // き + ゃ -> kya，し + ゃ -> sha
+            for kana in "きぎしじちぢにひびぴみり".chars() {
+                let base = map[&kana.to_string()].trim_end_matches('i').to_string();
+                let base = if base.ends_with("sh") || base.ends_with("ch") || base == "j" { base } else { base + "y" };
+                for (small, vowel) in [('ゃ', 'a'), ('ゅ', 'u'), ('ょ', 'o')] {
+                    map.insert(format!("{}{}", kana, small), format!("{}{}", base, vowel));
                 }
             }
+            map
         });
-        (chinese_cnt, String::from_utf8(res).unwrap())
+
+        // 片假名统一转换为平假名处理
+        let chars: Vec<char> = s.chars().map(|cp| match cp {
+            '\u{30A1}'..='\u{30F6}' | '\u{30FD}'..='\u{30FE}' => char::from_u32(cp as u32 - 0x60).unwrap(),
+            _ => cp,
+        }).collect();
+
+        let syllable = |idx: usize| -> Option<(&String, usize)> {
+            if idx + 1 < chars.len() {
+                if let Some(r) = map.get(&chars[idx..idx + 2].iter().collect::<String>()) {
+                    return Some((r, 2));
+                }
+            }
+            map.get(&chars.get(idx)?.to_string()).map(|r| (r, 1))
+        };
+
+        let mut res = String::new();
+        let mut sokuon = false;
+        let mut idx = 0;
+        while idx < chars.len() {
+            match chars[idx] {
+                // 促音：重复下一个音节的辅音，ch 前写作 t
+                'っ' => sokuon = true,
+                // 长音：重复前一个元音
+                'ー' => {
+                    if let Some(v) = res.chars().last().filter(|c| "aiueo".contains(*c)) {
+                        res.push(v);
+                    }
+                }
+                // 踊り字：重复前一个假名
+                'ゝ' | 'ゞ' if idx > 0 => {
+                    if let Some((r, _)) = syllable(idx - 1) {
+                        res.push_str(r);
+                    }
+                }
+                cp => match syllable(idx) {
+                    Some((r, len)) => {
+                        if sokuon {
+                            res.push(if r.starts_with("ch") { 't' } else { r.chars().next().unwrap() });
+                            sokuon = false;
+                        }
+                        res.push_str(r);
+                        // 撥音后面接元音或 y 时写作 n'
+                        if r == "n" {
+                            if let Some((next, _)) = syllable(idx + len) {
+                                if next.starts_with(|c: char| "aiueoy".contains(c)) {
+                                    res.push('\'');
+                                }
+                            }
+                        }
+                        idx += len;
+                        continue;
+                    }
+                    None => res.push(cp),
+                },
+            }
+            idx += 1;
+        }
+        res
+    }
+
+    /// 转换结果中的一段：(提示文本, 需要输入的文本)，排版时提示文本显示在对应的输入文本上方
+    pub type Segment = (String, String);
+
+    /// 转换词库中的一个词，支持两种读音标注：
+    /// + `重庆|chongqing`、`東京|とうきょう`：整个词的输入目标，替代自动转换
+    /// + `{重|chong}庆`、`{東京|とうきょう}に行く`：只标注词的一部分，其余部分自动转换
+    ///
+    /// 标注中的假名会转换为罗马字。整词的拼音读音在注音方案下按字切分后转换为注音，
+    /// 切分不唯一或者在粤拼方案下时返回 None；文本或读音为空等格式错误时也返回 None
+    pub fn transform_word(word: &str, scheme: Scheme) -> Option<Vec<Segment>> {
+        if !word.contains('{') {
+            return match word.split_once('|') {
+                Some((text, reading)) if text.is_empty() || reading.is_empty() => None,
+                Some((text, reading)) if scheme != Scheme::Pinyin && is_pinyin(reading) => match scheme {
+                    Scheme::Zhuyin => {
+                        let chars: Vec<char> = text.chars().collect();
+                        let mut splits = split_pinyin(&chars, reading);
+                        if splits.len() != 1 {
+                            return None;
+                        }
+                        Some(splits.pop()?.into_iter().map(zhuyin_with_tone).collect())
+                    }
+                    // 粤拼读音表中每个字只有一个读音，无法按拼音标注选出正确的读音
+                    _ => None,
+                },
+                Some((text, reading)) => {
+                    let target = transform_with(reading, scheme)?.into_iter().map(|(_, t)| t).collect();
+                    Some(vec![(text.to_string(), target)])
+                }
+                None => transform_with(word, scheme),
+            };
+        }
+
+        let mut segments = Vec::new();
+        let mut rest = word;
+        while !rest.is_empty() {
+            let (plain, ruby) = match rest.split_once('{') {
+                Some((plain, ruby)) => (plain, Some(ruby)),
+                None => (rest, None),
+            };
+            segments.append(&mut transform_with(plain, scheme)?);
+
+            let ruby = match ruby {
+                Some(ruby) => ruby,
+                None => break,
+            };
+            let (annotation, remain) = ruby.split_once('}')?;
+            let (text, reading) = annotation.split_once('|').filter(|(text, reading)| !text.is_empty() && !reading.is_empty())?;
+            segments.append(&mut transform_ruby(text, reading, scheme)?);
+            rest = remain;
+        }
+        Some(segments)
+    }
+
+    /// 读音是否为不带声调的拼音
+    fn is_pinyin(reading: &str) -> bool {
+        reading.chars().all(|c| c.is_ascii_lowercase() || c == 'ü')
+    }
+
+    /// 把整个词的拼音读音按字切分，返回所有切分方式，每种切分是每个字声调数字在末尾的拼音。
+    /// 同一个不带声调的读音有多个声调时优先取常用读音，否则每个声调各算一种切分
+    fn split_pinyin(chars: &[char], reading: &str) -> Vec<Vec<&'static str>> {
+        let (cp, rest) = match chars.split_first() {
+            Some(split) => split,
+            None if reading.is_empty() => return vec![Vec::new()],
+            None => return Vec::new(),
+        };
+        let (default, multi) = match (cp.to_pinyin(), cp.to_pinyin_multi()) {
+            (Some(default), Some(multi)) => (default, multi),
+            _ => return Vec::new(),
+        };
+        let mut candidates: Vec<(&str, &str)> = Vec::new();
+        for pinyin in multi.into_iter() {
+            let toned = if pinyin.plain() == default.plain() { default.with_tone_num_end() } else { pinyin.with_tone_num_end() };
+            if !candidates.contains(&(pinyin.plain(), toned)) {
+                candidates.push((pinyin.plain(), toned));
+            }
+        }
+
+        let mut splits = Vec::new();
+        for (plain, toned) in candidates {
+            if let Some(remain) = reading.strip_prefix(plain) {
+                for mut split in split_pinyin(rest, remain) {
+                    split.insert(0, toned);
+                    splits.push(split);
+                }
+            }
+        }
+        splits
+    }
+
+    /// 转换 `{字|读音}` 形式的标注。单个汉字标注的字母读音视为拼音，末尾可以带声调数字，
+    /// 注音方案下转换为注音符号，粤拼方案下忽略标注，使用粤拼读音表
+    fn transform_ruby(text: &str, reading: &str, scheme: Scheme) -> Option<Vec<Segment>> {
+        let mut chars = text.chars();
+        let single_hanzi = matches!((chars.next(), chars.next()), (Some(cp), None) if is_chinese(&cp));
+        let plain = reading.trim_end_matches(|c| matches!(c, '1'..='4'));
+        if single_hanzi && is_pinyin(plain) {
+            return match scheme {
+                Scheme::Pinyin => Some(vec![(text.to_string(), plain.to_string())]),
+                // 没有标声调时从多音字中找到与标注一致的读音
+                Scheme::Zhuyin if plain != reading => Some(vec![zhuyin_with_tone(reading)]),
+                Scheme::Zhuyin => {
+                    let pinyin = pinyin_with_tone(&text.chars().next()?, reading)?;
+                    Some(vec![zhuyin_with_tone(pinyin)])
+                }
+                Scheme::Jyutping => transform_with(text, scheme),
+            };
+        }
+        let target = transform_with(reading, scheme)?.into_iter().map(|(_, t)| t).collect();
+        Some(vec![(text.to_string(), target)])
+    }
+
+    /// 按输入方案转换，每个汉字、每段假名、每个其他字符各为一段，
+    /// 存在无法转换的汉字时返回 None
+    pub fn transform_with(s: &str, scheme: Scheme) -> Option<Vec<Segment>> {
+        let mut segments = Vec::new();
+        let mut rest = s;
+        let mut prev = None;
+        while let Some(cp) = rest.chars().next() {
+            if is_kana(&cp) {
+                let len = rest.find(|c: char| !is_kana(&c)).unwrap_or(rest.len());
+                let kana = &rest[..len];
+                segments.push((kana.to_string(), kana_to_romaji(kana)));
+                prev = kana.chars().last();
+                rest = &rest[len..];
+                continue;
+            }
+            if let Some((len, punctuation)) = match_punctuation(rest).or_else(|| match_cjk_punctuation(rest, prev)) {
+                segments.push((rest[..len].to_string(), punctuation.to_string()));
+                prev = rest[..len].chars().last();
+                rest = &rest[len..];
+                continue;
+            }
+            prev = Some(cp);
+            rest = &rest[cp.len_utf8()..];
+            if !is_chinese(&cp) {
+                segments.push((cp.to_string(), cp.to_string()));
+                continue;
+            }
+            let segment = match scheme {
+                Scheme::Pinyin => (cp.to_string(), cp.to_pinyin()?.plain().to_string()),
+                Scheme::Zhuyin => transform_zhuyin(&cp)?,
+                Scheme::Jyutping => (cp.to_string(), transform_jyutping(&cp)?),
+            };
+            segments.push(segment);
+        }
+        Some(segments)
     }
 
     // pub fn tokenize(s: &str) -> Vec<&str> {
@@ -83,9 +699,18 @@ pub mod util {
 mod test {
     use super::{util::*};
 
+    /// 拼接转换结果，返回 (提示文本, 需要输入的文本)
+    fn join(segments: Vec<Segment>) -> (String, String) {
+        segments.into_iter().fold((String::new(), String::new()), |(mut hint, mut target), (h, t)| {
+            hint.push_str(&h);
+            target.push_str(&t);
+            (hint, target)
+        })
+    }
+
     #[test]
     fn test_is_chinese() {
-        assert_eq!(is_chinese(&'曁'), true);
+        assert!(is_chinese(&'曁'));
     }
 
     #[test]
@@ -93,19 +718,28 @@ mod test {
         // let c = '》';
         let punctuaton = "《》。，；‘’：“”";
         let en = "<>.,;\'\':\"\"";
-        let mut res = Vec::new();
-        for c in punctuaton.chars().into_iter() {
+        let mut res = String::new();
+        for c in punctuaton.chars() {
             println!("{} = {}, {}", c, c.escape_unicode(), is_chinese(&c));
-            res.push(transform_punctuation(&c).unwrap());
+            res.push_str(transform_punctuation(&c).unwrap());
         }
-        assert_eq!(String::from_utf8(res).unwrap(), en);
+        assert_eq!(res, en);
+
+        let (cnt, res) = transform("【注意】等等……——、ＡＢＣ");
+        assert_eq!(res, "[zhuyi]dengdeng...--,ABC");
+        assert_eq!(cnt, 14);
+        // 英文中的破折号和省略号保持原样
+        let (cnt, res) = transform("wait… 他说——好");
+        assert_eq!((cnt, res.as_str()), (5, "wait… tashuo--hao"));
+        let (cnt, res) = transform("l·l 卡尔·马克思");
+        assert_eq!((cnt, res.as_str()), (6, "l·l kaer.makesi"));
     }
     #[test]
     fn test_transform_pinyin() {
         let s = "测试一下拼音!!asdji";
         let pinyin = "ceshiyixiapinyin";
         let mut pinyin_list = Vec::new();
-        s.chars().filter(|ch| is_chinese(ch)).for_each(|ch| {
+        s.chars().filter(is_chinese).for_each(|ch| {
             let p = &mut transform_pinyin(&ch);
             pinyin_list.append(p);
         });
@@ -114,6 +748,17 @@ mod test {
         assert_eq!(res, pinyin);
     }
 
+    #[test]
+    fn test_count_chinese() {
+        assert_eq!(count_chinese("长城，"), 2);
+        assert_eq!(count_chinese("【注意】等等……——、ＡＢＣ"), 4);
+        assert_eq!(count_chinese("重庆|chongqing"), 2);
+        assert_eq!(count_chinese("{重|chong}庆"), 2);
+        assert_eq!(count_chinese("東京|とうきょう"), 2);
+        assert_eq!(count_chinese("rust"), 0);
+        assert_eq!(count_chinese("ひらがな|hiragana"), 0);
+    }
+
     #[test]
     fn test_transform() {
         let s = "①子曰：“不仁者不可以久处约，不可以长处乐。仁者安仁，知者利仁。”";
@@ -123,6 +768,99 @@ mod test {
         // assert_eq!(res, t);
     }
 
+    #[test]
+    fn test_pinyin_to_zhuyin() {
+        let cases = [
+            ("zhong", "ㄓㄨㄥ"), ("shi", "ㄕ"), ("yu", "ㄩ"), ("you", "ㄧㄡ"),
+            ("wei", "ㄨㄟ"), ("jue", "ㄐㄩㄝ"), ("lü", "ㄌㄩ"), ("gui", "ㄍㄨㄟ"),
+            ("dun", "ㄉㄨㄣ"), ("liu", "ㄌㄧㄡ"), ("er", "ㄦ"), ("yong", "ㄩㄥ"),
+        ];
+        for (pinyin, zhuyin) in cases {
+            assert_eq!(pinyin_to_zhuyin(pinyin), zhuyin);
+        }
+    }
+
+    #[test]
+    fn test_transform_zhuyin() {
+        let segments = transform_with("中文，", Scheme::Zhuyin).unwrap();
+        assert_eq!(segments[0], (String::from("ㄓㄨㄥ"), String::from("5j/ ")));
+        let (hint, keys) = join(segments);
+        assert_eq!(hint, "ㄓㄨㄥㄨㄣˊ，");
+        assert_eq!(keys, "5j/ jp6,");
+        // 三声、四声和轻声
+        assert_eq!(zhuyin_with_tone("hao3"), (String::from("ㄏㄠˇ"), String::from("cl3")));
+        assert_eq!(zhuyin_with_tone("lü4"), (String::from("ㄌㄩˋ"), String::from("xm4")));
+        assert_eq!(zhuyin_with_tone("de"), (String::from("˙ㄉㄜ"), String::from("2k7")));
+    }
+
+    #[test]
+    fn test_transform_jyutping() {
+        let segments = transform_with("风花雪月", Scheme::Jyutping).unwrap();
+        assert_eq!(segments.len(), 4);
+        let (hint, target) = join(segments);
+        assert_eq!(hint, "风花雪月");
+        assert_eq!(target, "fungfaasyutjyut");
+        // 部首不是 Unihan 收录的汉字，没有粤语读音
+        assert!(transform_with("⺀", Scheme::Jyutping).is_none());
+    }
+
+    #[test]
+    fn test_kana_to_romaji() {
+        let cases = [
+            ("とうきょう", "toukyou"), ("しんぶん", "shinbun"), ("きっぷ", "kippu"),
+            ("まっちゃ", "matcha"), ("コーヒー", "koohii"), ("パーティー", "paatii"),
+            ("ほんや", "hon'ya"), ("ぎゅうにゅう", "gyuunyuu"), ("ファイル", "fairu"),
+        ];
+        for (kana, romaji) in cases {
+            assert_eq!(kana_to_romaji(kana), romaji);
+        }
+    }
+
+    #[test]
+    fn test_transform_word() {
+        let segments = transform_word("東京|とうきょう", Scheme::Pinyin).unwrap();
+        assert_eq!(segments, vec![(String::from("東京"), String::from("toukyou"))]);
+        let (hint, target) = join(transform_word("さくら", Scheme::Pinyin).unwrap());
+        assert_eq!((hint.as_str(), target.as_str()), ("さくら", "sakura"));
+    }
+
+    #[test]
+    fn test_transform_annotation() {
+        let (hint, target) = join(transform_word("重庆|chongqing", Scheme::Pinyin).unwrap());
+        assert_eq!((hint.as_str(), target.as_str()), ("重庆", "chongqing"));
+        let segments = transform_word("{重|chong}庆市", Scheme::Pinyin).unwrap();
+        assert_eq!(segments[0], (String::from("重"), String::from("chong")));
+        let (hint, target) = join(segments);
+        assert_eq!((hint.as_str(), target.as_str()), ("重庆市", "chongqingshi"));
+        let (hint, target) = join(transform_word("{乐|lao4}亭", Scheme::Pinyin).unwrap());
+        assert_eq!((hint.as_str(), target.as_str()), ("乐亭", "laoting"));
+        let (hint, target) = join(transform_word("{乐|lao4}亭", Scheme::Zhuyin).unwrap());
+        assert_eq!((hint.as_str(), target.as_str()), ("ㄌㄠˋㄊㄧㄥˊ", "xl4wu/6"));
+        let (hint, target) = join(transform_word("{长|chang}城", Scheme::Zhuyin).unwrap());
+        assert_eq!((hint.as_str(), target.as_str()), ("ㄔㄤˊㄔㄥˊ", "t;6t/6"));
+        let (hint, target) = join(transform_word("{東京|とうきょう}へ", Scheme::Pinyin).unwrap());
+        assert_eq!((hint.as_str(), target.as_str()), ("東京へ", "toukyouhe"));
+        assert!(transform_word("{重|chong庆", Scheme::Pinyin).is_none());
+        // 整词的拼音读音在注音方案下按字切分
+        let (hint, target) = join(transform_word("重庆|chongqing", Scheme::Zhuyin).unwrap());
+        assert_eq!((hint.as_str(), target.as_str()), ("ㄔㄨㄥˊㄑㄧㄥˋ", "tj/6fu/4"));
+        let (hint, _) = join(transform_word("长大|zhangda", Scheme::Zhuyin).unwrap());
+        assert_eq!(hint, "ㄓㄤˇㄉㄚˋ");
+        assert!(transform_word("重庆|zhongqin", Scheme::Zhuyin).is_none());
+        assert!(transform_word("重庆|chongqing", Scheme::Jyutping).is_none());
+        // 文本或读音为空
+        for word in ["重庆|", "|abc", "{重|}庆", "{|chong}庆"] {
+            assert!(transform_word(word, Scheme::Pinyin).is_none(), "{}", word);
+        }
+    }
+
+    #[test]
+    fn test_transform_unicode() {
+        let (cnt, res) = transform("Straße — café");
+        assert_eq!((cnt, res.as_str()), (0, "Straße — café"));
+        assert_eq!(to_ascii("Straße — café, Ærø"), "Strasse - cafe, AEro");
+    }
+
     // #[test]
     // fn test_tokenize() {
     //     let token_list = tokenize("毫无疑问，银行系统的去杠杆化程度既不能满足监管者，也不能令市场满意，这限制了信用增长的势头。");